@@ -0,0 +1,110 @@
+//! A simple benchmark harness, which runs registered solutions over their real
+//! puzzle input a number of times and reports on how long parsing and solving
+//! took. Reports can be written out as JSON, so that they can be compared
+//! between commits.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::registry::{Part, Solution};
+use crate::util::*;
+
+/// How long a single run of a single part took.
+#[derive(Debug, Clone, Copy)]
+pub struct Timing {
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+impl Timing {
+    /// Parse `input` for solution `S` and run `part` on it, timing each phase.
+    pub(crate) fn measure<S: Solution>(part: u8, input: &str) -> anyhow::Result<Self> {
+        let start = Instant::now();
+        let input = S::parse(input)?;
+        let parsed = Instant::now();
+        match part {
+            1 => drop(std::hint::black_box(S::part1(input))),
+            _ => drop(std::hint::black_box(S::part2(input))),
+        }
+        let solved = Instant::now();
+        Ok(Timing {
+            parse: parsed - start,
+            solve: solved - parsed,
+        })
+    }
+}
+
+/// Summary statistics over a number of runs, all in nanoseconds.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Stats {
+    pub min: u128,
+    pub median: u128,
+    pub p90: u128,
+    pub p99: u128,
+    pub max: u128,
+}
+
+impl Stats {
+    fn compute(durations: impl IntoIterator<Item = Duration>) -> Self {
+        let nanos = durations
+            .into_iter()
+            .map(|d| d.as_nanos())
+            .sorted()
+            .collect_vec();
+        // Nearest-rank percentiles, which are always one of the samples.
+        let percentile = |p: usize| {
+            let rank = (p * nanos.len()).div_ceil(100).max(1);
+            nanos.get(rank - 1).copied().unwrap_or(0)
+        };
+        Stats {
+            min: percentile(0),
+            median: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: percentile(100),
+        }
+    }
+}
+
+/// The outcome of benchmarking one part of one day.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub runs: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |nanos: u128| nanos as f64 / 1e6;
+        write!(
+            f,
+            "{} day {:2} part {}: parse {:10.3}ms (p90 {:10.3}ms), solve {:10.3}ms (p90 {:10.3}ms)",
+            self.year,
+            self.day,
+            self.part,
+            ms(self.parse.median),
+            ms(self.parse.p90),
+            ms(self.solve.median),
+            ms(self.solve.p90),
+        )
+    }
+}
+
+/// Run `part` over `input` the given number of times and summarize the
+/// timings.
+pub fn bench(part: &Part, input: &str, runs: usize) -> anyhow::Result<Report> {
+    let timings: Vec<Timing> = (0..runs).map(|_| (part.time)(input)).try_collect()?;
+    Ok(Report {
+        year: part.year,
+        day: part.day,
+        part: part.part,
+        runs,
+        parse: Stats::compute(timings.iter().map(|t| t.parse)),
+        solve: Stats::compute(timings.iter().map(|t| t.solve)),
+    })
+}
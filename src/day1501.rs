@@ -1,17 +1,33 @@
 use crate::util::*;
 
-aoc_test!(part1, 150100, 232);
-aoc_test!(part2, 150100, 1783);
+aoc_register!(Day1501);
+aoc_test!(Day1501);
 
-fn part1(moves: String) -> i32 {
-    floors(moves.as_str()).last().unwrap()
-}
+struct Day1501;
+
+impl Solution for Day1501 {
+    type Input = String;
+    type Output1 = i32;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2015
+    }
+
+    fn day() -> u8 {
+        1
+    }
+
+    fn part1(moves: String) -> i32 {
+        floors(moves.as_str()).last().unwrap()
+    }
 
-fn part2(moves: String) -> usize {
-    let n_floors = floors(moves.as_str())
-        .take_while(|&floor| floor >= 0)
-        .count();
-    n_floors + 1
+    fn part2(moves: String) -> usize {
+        let n_floors = floors(moves.as_str())
+            .take_while(|&floor| floor >= 0)
+            .count();
+        n_floors + 1
+    }
 }
 
 fn floors(moves: &str) -> impl Iterator<Item = i32> + '_ {
@@ -2,8 +2,8 @@ use crate::util::*;
 use std::num::ParseIntError;
 use std::str::FromStr;
 
-aoc_test!(part1, 150200, 1606483);
-aoc_test!(part2, 150200, 3842356);
+aoc_register!(Day1502);
+aoc_test!(Day1502);
 
 #[derive(From)]
 struct Gifts {
@@ -26,12 +26,28 @@ impl FromStr for Gifts {
     }
 }
 
-fn part1(gifts: Gifts) -> u32 {
-    gifts.sum(Gift::wrap)
-}
+struct Day1502;
+
+impl Solution for Day1502 {
+    type Input = Gifts;
+    type Output1 = u32;
+    type Output2 = u32;
 
-fn part2(gifts: Gifts) -> u32 {
-    gifts.sum(Gift::ribbon)
+    fn year() -> u16 {
+        2015
+    }
+
+    fn day() -> u8 {
+        2
+    }
+
+    fn part1(gifts: Gifts) -> u32 {
+        gifts.sum(Gift::wrap)
+    }
+
+    fn part2(gifts: Gifts) -> u32 {
+        gifts.sum(Gift::ribbon)
+    }
 }
 
 impl Gifts {
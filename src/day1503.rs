@@ -1,41 +1,60 @@
 use crate::util::*;
 
-aoc_test!(part1, 150300, 2572);
-aoc_test!(part2, 150300, 2631);
+use aoc_grid::*;
 
-fn part1(turns: String) -> usize {
-    let mut book = Book::default();
-    book.process_turns(turns.chars());
-    book.count()
-}
+aoc_register!(Day1503);
+aoc_test!(Day1503);
+
+struct Day1503;
+
+impl Solution for Day1503 {
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2015
+    }
 
-fn part2(turns: String) -> usize {
-    let mut book = Book::default();
-    let santa_turns = turns.chars().step_by(2);
-    let robo_santa_turns = turns.chars().skip(1).step_by(2);
-    book.process_turns(santa_turns);
-    book.process_turns(robo_santa_turns);
-    book.count()
+    fn day() -> u8 {
+        3
+    }
+
+    fn part1(turns: String) -> usize {
+        let mut book = Book::default();
+        book.process_turns(turns.chars());
+        book.count()
+    }
+
+    fn part2(turns: String) -> usize {
+        let mut book = Book::default();
+        let santa_turns = turns.chars().step_by(2);
+        let robo_santa_turns = turns.chars().skip(1).step_by(2);
+        book.process_turns(santa_turns);
+        book.process_turns(robo_santa_turns);
+        book.count()
+    }
 }
 
 #[derive(Default)]
 struct Book {
-    visited: HashSet<(isize, isize)>,
+    visited: HashSet<Point>,
 }
 
 impl Book {
     fn process_turns(&mut self, turns: impl Iterator<Item = char>) {
-        let (mut x, mut y) = (0, 0);
-        self.visited.insert((x, y));
+        let mut house = Point::ORIGIN;
+        self.visited.insert(house);
         for turn in turns {
-            match turn {
-                '>' => x += 1,
-                '<' => x -= 1,
-                'v' => y += 1,
-                '^' => y -= 1,
-                _ => {}
-            }
-            self.visited.insert((x, y));
+            let dir = match turn {
+                '>' => Dir::E,
+                '<' => Dir::W,
+                'v' => Dir::S,
+                '^' => Dir::N,
+                _ => continue,
+            };
+            house = house.walk(dir);
+            self.visited.insert(house);
         }
     }
 
@@ -1,14 +1,30 @@
 use crate::util::*;
 
-aoc_test!(part1, 150400, 346386);
-aoc_test!(part2, 150400, 9958218);
+aoc_register!(Day1504);
+aoc_test!(Day1504);
 
-fn part1(input: String) -> usize {
-    Mine::new(5, input.into_bytes()).mine()
-}
+struct Day1504;
+
+impl Solution for Day1504 {
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
 
-fn part2(input: String) -> usize {
-    Mine::new(6, input.into_bytes()).mine()
+    fn year() -> u16 {
+        2015
+    }
+
+    fn day() -> u8 {
+        4
+    }
+
+    fn part1(input: String) -> usize {
+        Mine::new(5, input.into_bytes()).mine()
+    }
+
+    fn part2(input: String) -> usize {
+        Mine::new(6, input.into_bytes()).mine()
+    }
 }
 
 struct Mine {
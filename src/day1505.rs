@@ -1,9 +1,7 @@
 use crate::util::*;
 
-aoc_test!(part1, 150501, 2);
-aoc_test!(part1, 150500, 236);
-aoc_test!(part2, 150502, 2);
-aoc_test!(part2, 150500, 51);
+aoc_register!(Day1505);
+aoc_test!(Day1505);
 
 struct Text {
     lines: Vec<Line>,
@@ -26,12 +24,28 @@ impl From<&str> for Line {
     }
 }
 
-fn part1(text: Text) -> usize {
-    text.lines.into_iter().filter(Line::nice_1).count()
-}
+struct Day1505;
+
+impl Solution for Day1505 {
+    type Input = Text;
+    type Output1 = usize;
+    type Output2 = usize;
 
-fn part2(text: Text) -> usize {
-    text.lines.into_iter().filter(Line::nice_2).count()
+    fn year() -> u16 {
+        2015
+    }
+
+    fn day() -> u8 {
+        5
+    }
+
+    fn part1(text: Text) -> usize {
+        text.lines.into_iter().filter(Line::nice_1).count()
+    }
+
+    fn part2(text: Text) -> usize {
+        text.lines.into_iter().filter(Line::nice_2).count()
+    }
 }
 
 impl Line {
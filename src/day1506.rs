@@ -2,8 +2,8 @@ use ndarray::{s, Array2, ArrayViewMut2};
 
 use crate::util::*;
 
-aoc_test!(part1, 150600, 400410);
-aoc_test!(part2, 150600, 15343601);
+aoc_register!(Day1506);
+aoc_test!(Day1506);
 
 #[derive(Debug, From)]
 struct Instructions(Vec<Instruction>);
@@ -61,28 +61,44 @@ impl Instruction {
     }
 }
 
-fn part1(instructions: Instructions) -> usize {
-    let mut bulbs = Array2::from_elem((1000, 1000), false);
-    instructions.execute(&mut bulbs, |operation, bulb| {
-        *bulb = match (&operation, *bulb) {
-            (Operation::Toggle, true) => false,
-            (Operation::Toggle, false) => true,
-            (Operation::On, _) => true,
-            (Operation::Off, _) => false,
-        };
-    });
-    bulbs.into_iter().filter(|&b| b).count()
-}
+struct Day1506;
+
+impl Solution for Day1506 {
+    type Input = Instructions;
+    type Output1 = usize;
+    type Output2 = u64;
 
-fn part2(instructions: Instructions) -> u64 {
-    let mut bulbs = Array2::from_elem((1000, 1000), 0u64);
-    instructions.execute(&mut bulbs, |operation, bulb| {
-        let change: i64 = match &operation {
-            Operation::Toggle => 2,
-            Operation::On => 1,
-            Operation::Off => -1,
-        };
-        *bulb = bulb.saturating_add_signed(change);
-    });
-    bulbs.into_iter().sum()
+    fn year() -> u16 {
+        2015
+    }
+
+    fn day() -> u8 {
+        6
+    }
+
+    fn part1(instructions: Instructions) -> usize {
+        let mut bulbs = Array2::from_elem((1000, 1000), false);
+        instructions.execute(&mut bulbs, |operation, bulb| {
+            *bulb = match (&operation, *bulb) {
+                (Operation::Toggle, true) => false,
+                (Operation::Toggle, false) => true,
+                (Operation::On, _) => true,
+                (Operation::Off, _) => false,
+            };
+        });
+        bulbs.into_iter().filter(|&b| b).count()
+    }
+
+    fn part2(instructions: Instructions) -> u64 {
+        let mut bulbs = Array2::from_elem((1000, 1000), 0u64);
+        instructions.execute(&mut bulbs, |operation, bulb| {
+            let change: i64 = match &operation {
+                Operation::Toggle => 2,
+                Operation::On => 1,
+                Operation::Off => -1,
+            };
+            *bulb = bulb.saturating_add_signed(change);
+        });
+        bulbs.into_iter().sum()
+    }
 }
@@ -1,5 +1,3 @@
-use std::borrow::Borrow;
-
 use crate::util::*;
 use test_case::test_case;
 
@@ -24,6 +22,9 @@ enum Value {
     Wire(String),
 }
 
+aoc_register!(Day1507);
+aoc_test!(Day1507);
+
 #[test_case("d", 72)]
 #[test_case("e", 507)]
 #[test_case("f", 492)]
@@ -33,72 +34,306 @@ enum Value {
 #[test_case("x", 123)]
 #[test_case("y", 456)]
 fn test(wire: &str, word: u16) {
-    let mut machine = Machine::from_instructions(parse_test_file(150701));
-    assert_eq!(machine.run(wire), word);
+    let mut circuit = Circuit::from_instructions(parse_test_file(150701));
+    assert_eq!(circuit.eval(wire).unwrap(), word);
+}
+
+#[test]
+fn test_override() {
+    let mut circuit = Circuit::from_instructions(parse_test_file(150701));
+    assert_eq!(circuit.eval("d").unwrap(), 72);
+    assert_eq!(circuit.eval("g").unwrap(), 114);
+    circuit.set("x", 0xffff);
+    assert_eq!(circuit.eval("d").unwrap(), 456);
+    assert_eq!(circuit.eval("g").unwrap(), 114);
+    circuit.unset("x");
+    assert_eq!(circuit.eval("d").unwrap(), 72);
 }
 
-aoc_test!(part1, 150700, 956);
-aoc_test!(part2, 150700, 40149);
+#[test]
+fn test_round_trip() {
+    let text: String = parse_test_file(150700);
+    let instructions: Instructions = text.parse().unwrap();
+    assert_eq!(instructions.to_string(), text.trim_end());
+}
+
+#[test]
+fn test_analysis() {
+    let circuit = Circuit::from_instructions(parse_test_file(150701));
+    assert_eq!(circuit.dead_wires("d"), ["e", "f", "g", "h", "i"]);
+    assert!(circuit.to_dot().contains("    x -> d;\n"));
+    let folded = circuit.fold(&["y"]).unwrap();
+    assert_eq!(
+        folded.to_string(),
+        "123 AND y -> d\n123 OR y -> e\ny RSHIFT 2 -> g\nNOT y -> i\n456 -> y"
+    );
+}
 
-fn part1(instructions: Instructions) -> u16 {
-    Machine::from_instructions(instructions).run("a")
+#[test]
+fn test_fold() {
+    let circuit = Circuit::from_instructions(parse_test_file(150700));
+    let mut folded = Circuit::from_instructions(circuit.fold(&["b"]).unwrap());
+    assert!(folded.netlist.len() < circuit.netlist.len());
+    assert_eq!(folded.eval("a").unwrap(), 956);
+    folded.set("b", 956);
+    assert_eq!(folded.eval("a").unwrap(), 40149);
 }
 
-fn part2(instructions: Instructions) -> u16 {
-    let machine = &mut Machine::from_instructions(instructions);
-    machine.hardwire("b", 956);
-    machine.run("a")
+#[test]
+fn test_loop() {
+    let instructions = "x AND b -> y\n1 -> b\ny -> z\nNOT z -> x\nz -> a"
+        .parse()
+        .unwrap();
+    let mut circuit = Circuit::from_instructions(instructions);
+    let error = circuit.eval("a").unwrap_err();
+    assert_eq!(error.to_string(), "combinational loop: x -> y -> z -> x");
+}
+
+struct Day1507;
+
+impl Solution for Day1507 {
+    type Input = Instructions;
+    type Output1 = u16;
+    type Output2 = u16;
+
+    fn year() -> u16 {
+        2015
+    }
+
+    fn day() -> u8 {
+        7
+    }
+
+    fn part1(instructions: Instructions) -> u16 {
+        let mut circuit = Circuit::from_instructions(instructions);
+        circuit.eval("a").expect("broken circuit")
+    }
+
+    /// Feed the signal on `a` back into `b`, and see what `a` becomes
+    fn part2(instructions: Instructions) -> u16 {
+        let mut circuit = Circuit::from_instructions(instructions);
+        let a = circuit.eval("a").expect("broken circuit");
+        circuit.set("b", a);
+        circuit.eval("a").expect("broken circuit")
+    }
 }
 
-struct Machine {
-    program: HashMap<String, Op>,
-    cache: HashMap<String, u16>,
+/// A circuit of gates, each of which drives a single wire. The netlist never
+/// changes; instead, any wire can be overridden with a fixed signal, and we
+/// keep track of which signals need to be recomputed when that happens.
+#[derive(Clone)]
+struct Circuit {
+    netlist: HashMap<String, Op>,
+    /// For each wire, the wires driven by gates that it is an input of
+    dependents: HashMap<String, Vec<String>>,
+    overrides: HashMap<String, u16>,
+    signals: HashMap<String, u16>,
 }
 
-impl Machine {
-    fn from_instructions(instructions: Instructions) -> Self {
-        let cache = HashMap::new();
-        let program = instructions
-            .0
+impl Circuit {
+    fn from_instructions(Instructions(instructions): Instructions) -> Self {
+        let netlist: HashMap<String, Op> = instructions
             .into_iter()
             .map(|Instruction(op, wire)| (wire, op))
             .collect();
-        Machine { program, cache }
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        for (wire, op) in netlist.iter() {
+            for input in op.inputs() {
+                dependents
+                    .entry(input.to_string())
+                    .or_default()
+                    .push(wire.clone());
+            }
+        }
+        Circuit {
+            netlist,
+            dependents,
+            overrides: HashMap::new(),
+            signals: HashMap::new(),
+        }
+    }
+
+    /// Force the signal on `wire` to be `word`, regardless of its gate
+    fn set(&mut self, wire: &str, word: u16) {
+        self.invalidate(wire);
+        self.overrides.insert(wire.to_string(), word);
     }
 
-    fn hardwire(&mut self, wire: &str, word: u16) {
-        self.program
-            .insert(wire.to_string(), Op::Val(Value::Constant(word)));
+    /// Let the gate for `wire` drive it again
+    fn unset(&mut self, wire: &str) {
+        self.invalidate(wire);
+        self.overrides.remove(wire);
     }
 
-    fn run(&mut self, wire: &str) -> u16 {
-        if self.cache.contains_key(wire) {
-            *self.cache.get(wire).unwrap()
-        } else {
-            let word = self.exe(wire);
-            self.cache.insert(wire.to_string(), word);
-            word
+    /// Forget the signals on `wire` and everything downstream of it
+    fn invalidate(&mut self, wire: &str) {
+        let downstream = |wire: &String| self.dependents.get(wire).into_iter().flatten().cloned();
+        let search = aoc::search::bfs([wire.to_string()], downstream, |_| false);
+        for wire in search.reached() {
+            self.signals.remove(wire);
         }
     }
 
-    fn exe(&mut self, wire: &str) -> u16 {
-        use Op::*;
-        let op = self.program.remove(wire).unwrap();
-        match op {
-            Val(x) => self.val(&x),
-            And(x, y) => self.val(&x) & self.val(&y),
-            Or(x, y) => self.val(&x) | self.val(&y),
-            Left(x, i) => self.val(&x) << i,
-            Right(x, i) => self.val(&x) >> i,
-            Not(x) => !self.val(&x),
+    fn signal(&self, wire: &str) -> Option<u16> {
+        self.overrides.get(wire).or(self.signals.get(wire)).copied()
+    }
+
+    /// Compute the signal on `wire`, along with any other signals it needs
+    fn eval(&mut self, wire: &str) -> anyhow::Result<u16> {
+        for wire in self.order(wire)? {
+            let word = self.netlist[&wire].eval(|wire| self.signal(wire).unwrap_or(0));
+            self.signals.insert(wire, word);
         }
+        self.signal(wire)
+            .ok_or_else(|| anyhow!("no signal on {wire}"))
     }
 
-    fn val(&mut self, value: &Value) -> u16 {
-        use Value::*;
-        match value {
-            Constant(word) => *word,
-            Wire(wire) => self.run(wire),
+    /// The wires that need computing before we know the signal on `wire`,
+    /// in an order such that every gate's inputs come before its output.
+    fn order(&self, wire: &str) -> anyhow::Result<Vec<String>> {
+        enum Mark {
+            Visiting,
+            Done,
+        }
+        let mut marks: HashMap<&str, Mark> = HashMap::new();
+        let mut order = vec![];
+        // The wires currently being visited, each an input of the previous
+        let mut path: Vec<&str> = vec![];
+        // A wire, and whether all its inputs have been pushed already
+        let mut stack = vec![(wire, false)];
+        while let Some((wire, expanded)) = stack.pop() {
+            if expanded {
+                marks.insert(wire, Mark::Done);
+                path.pop();
+                order.push(wire.to_string());
+                continue;
+            }
+            match marks.get(wire) {
+                Some(Mark::Done) => continue,
+                Some(Mark::Visiting) => {
+                    let start = path.iter().position(|&w| w == wire).unwrap_or(0);
+                    // Follow the signal around, rather than the dependencies
+                    let cycle = path[start..].iter().rev().chain(path.last()).join(" -> ");
+                    return Err(anyhow!("combinational loop: {cycle}"));
+                }
+                None => {}
+            }
+            if self.signal(wire).is_some() {
+                marks.insert(wire, Mark::Done);
+                continue;
+            }
+            let op = self
+                .netlist
+                .get(wire)
+                .ok_or_else(|| anyhow!("no gate drives {wire}"))?;
+            marks.insert(wire, Mark::Visiting);
+            path.push(wire);
+            stack.push((wire, true));
+            stack.extend(op.inputs().map(|input| (input, false)));
+        }
+        Ok(order)
+    }
+}
+
+/// Tools for looking at the structure of a circuit, rather than its signals
+impl Circuit {
+    /// Render the wire graph in Graphviz DOT format. Every wire is a node,
+    /// labelled with the gate that drives it, and every input is an edge.
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph circuit {\n");
+        for (wire, op) in self.netlist.iter().sorted_by_key(|(wire, _)| *wire) {
+            dot += &format!("    {wire} [label=\"{wire}\\n{op}\"];\n");
+            for input in op.inputs() {
+                dot += &format!("    {input} -> {wire};\n");
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+
+    /// Simplify the netlist, assuming that only the wires in `inputs` will
+    /// ever be overridden. Gates that don't depend on any of them always
+    /// output the same signal, so we fold those signals into the gates that
+    /// do, and leave out everything else.
+    fn fold(&self, inputs: &[&str]) -> anyhow::Result<Instructions> {
+        let downstream = |wire: &String| self.dependents.get(wire).into_iter().flatten().cloned();
+        let inputs = inputs.iter().map(|wire| wire.to_string());
+        let live: HashSet<String> = aoc::search::bfs(inputs, downstream, |_| false)
+            .reached()
+            .cloned()
+            .collect();
+        let mut fixed = self.clone();
+        let mut fold = |value: &Value| -> anyhow::Result<Value> {
+            Ok(match value.wire() {
+                Some(wire) if !live.contains(wire) => Value::Constant(fixed.eval(wire)?),
+                _ => value.clone(),
+            })
+        };
+        let mut instructions = vec![];
+        for wire in live
+            .iter()
+            .filter(|wire| self.netlist.contains_key(*wire))
+            .sorted()
+        {
+            let op = match &self.netlist[wire] {
+                Op::Val(x) => Op::Val(fold(x)?),
+                Op::And(x, y) => Op::And(fold(x)?, fold(y)?),
+                Op::Or(x, y) => Op::Or(fold(x)?, fold(y)?),
+                Op::Left(x, i) => Op::Left(fold(x)?, *i),
+                Op::Right(x, i) => Op::Right(fold(x)?, *i),
+                Op::Not(x) => Op::Not(fold(x)?),
+            };
+            instructions.push(Instruction(op, wire.clone()));
+        }
+        Ok(Instructions(instructions))
+    }
+
+    /// The wires that have no influence at all on the signal on `output`
+    fn dead_wires(&self, output: &str) -> Vec<&str> {
+        let upstream = |wire: &&str| self.netlist.get(*wire).into_iter().flat_map(Op::inputs);
+        let search = aoc::search::bfs([output], upstream, |_| false);
+        let live: HashSet<&str> = search.reached().copied().collect();
+        self.netlist
+            .keys()
+            .map(String::as_str)
+            .filter(|wire| !live.contains(wire))
+            .sorted()
+            .collect()
+    }
+}
+
+impl Op {
+    /// The wires this gate reads from
+    fn inputs(&self) -> impl Iterator<Item = &str> {
+        let (x, y) = match self {
+            Op::Val(x) | Op::Not(x) | Op::Left(x, _) | Op::Right(x, _) => (x, None),
+            Op::And(x, y) | Op::Or(x, y) => (x, Some(y)),
+        };
+        [Some(x), y].into_iter().flatten().flat_map(Value::wire)
+    }
+
+    /// The signal this gate outputs, given the signals on its inputs
+    fn eval(&self, signal: impl Fn(&str) -> u16) -> u16 {
+        let val = |value: &Value| match value {
+            Value::Constant(word) => *word,
+            Value::Wire(wire) => signal(wire),
+        };
+        match self {
+            Op::Val(x) => val(x),
+            Op::And(x, y) => val(x) & val(y),
+            Op::Or(x, y) => val(x) | val(y),
+            Op::Left(x, i) => val(x) << i,
+            Op::Right(x, i) => val(x) >> i,
+            Op::Not(x) => !val(x),
+        }
+    }
+}
+
+impl Value {
+    fn wire(&self) -> Option<&str> {
+        match self {
+            Value::Wire(wire) => Some(wire),
+            Value::Constant(_) => None,
         }
     }
 }
@@ -123,7 +358,6 @@ impl FromStr for Instructions {
         };
         let instruction = separated_pair(op, tok("->"), wire()).map(|(p, q)| Instruction(p, q));
         let instructions = separated_list1(multispace1, instruction).map(Instructions);
-        let instructions = terminated(instructions, tuple((multispace0, eof)));
         instructions.anyhow(s)
     }
 }
@@ -143,9 +377,23 @@ impl Display for Op {
             Op::Val(x) => write!(f, "{x}"),
             Op::Not(x) => write!(f, "NOT {x}"),
             Op::And(x, y) => write!(f, "{x} AND {y}"),
-            Op::Or(x, y) => write!(f, "{x} AND {y}"),
-            Op::Left(x, y) => write!(f, "{x} AND {y}"),
-            Op::Right(x, y) => write!(f, "{x} AND {y}"),
+            Op::Or(x, y) => write!(f, "{x} OR {y}"),
+            Op::Left(x, y) => write!(f, "{x} LSHIFT {y}"),
+            Op::Right(x, y) => write!(f, "{x} RSHIFT {y}"),
         }
     }
 }
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Instruction(op, wire) = self;
+        write!(f, "{op} -> {wire}")
+    }
+}
+
+/// Prints the netlist in the same format it's parsed from
+impl Display for Instructions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join("\n"))
+    }
+}
@@ -1,16 +1,30 @@
 use crate::util::*;
 
-aoc_test!(part1, 220101, 24000);
-aoc_test!(part1, 220100, 69206);
-aoc_test!(part2, 220101, 45000);
-aoc_test!(part2, 220100, 197400);
+aoc_register!(Day2201);
+aoc_test!(Day2201);
 
-fn part1(input: Chart) -> u32 {
-    sum_top(input, 1)
-}
+struct Day2201;
+
+impl Solution for Day2201 {
+    type Input = Chart;
+    type Output1 = u32;
+    type Output2 = u32;
 
-fn part2(input: Chart) -> u32 {
-    sum_top(input, 3)
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        1
+    }
+
+    fn part1(input: Chart) -> u32 {
+        sum_top(input, 1)
+    }
+
+    fn part2(input: Chart) -> u32 {
+        sum_top(input, 3)
+    }
 }
 
 fn sum_top(input: Chart, n: usize) -> u32 {
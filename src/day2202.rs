@@ -1,22 +1,36 @@
 use crate::util::*;
 
-aoc_test!(part1, 220201, 15);
-aoc_test!(part1, 220200, 10994);
-aoc_test!(part2, 220201, 12);
-aoc_test!(part2, 220200, 12526);
-
-fn part1(input: String) -> u32 {
-    let plays = input.lines().map(parse_line1);
-    let scores = plays.map(|(p, q)| score1(&p, &q));
-    let total: u32 = scores.sum();
-    total
-}
+aoc_register!(Day2202);
+aoc_test!(Day2202);
+
+struct Day2202;
+
+impl Solution for Day2202 {
+    type Input = String;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn year() -> u16 {
+        2022
+    }
 
-fn part2(input: String) -> u32 {
-    let plays = input.lines().map(parse_line2);
-    let scores = plays.map(|(p, q)| score2(&p, &q));
-    let total: u32 = scores.sum();
-    total
+    fn day() -> u8 {
+        2
+    }
+
+    fn part1(input: String) -> u32 {
+        let plays = input.lines().map(parse_line1);
+        let scores = plays.map(|(p, q)| score1(&p, &q));
+        let total: u32 = scores.sum();
+        total
+    }
+
+    fn part2(input: String) -> u32 {
+        let plays = input.lines().map(parse_line2);
+        let scores = plays.map(|(p, q)| score2(&p, &q));
+        let total: u32 = scores.sum();
+        total
+    }
 }
 
 #[derive(Copy, Clone)]
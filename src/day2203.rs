@@ -1,27 +1,41 @@
 use crate::util::*;
 
-aoc_test!(part1, 220301, 157);
-aoc_test!(part1, 220300, 7785);
-aoc_test!(part2, 220301, 70);
-aoc_test!(part2, 220300, 2633);
-
-fn part1(input: String) -> u32 {
-    input
-        .lines()
-        .map(|line| line.split_at(line.len() / 2))
-        .map(|(xx, yy)| intersect(&[xx, yy]))
-        .map(priority)
-        .sum()
-}
+aoc_register!(Day2203);
+aoc_test!(Day2203);
+
+struct Day2203;
+
+impl Solution for Day2203 {
+    type Input = String;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn year() -> u16 {
+        2022
+    }
 
-fn part2(input: String) -> u32 {
-    input
-        .lines()
-        .collect::<Vec<_>>()
-        .chunks(3)
-        .map(intersect)
-        .map(priority)
-        .sum()
+    fn day() -> u8 {
+        3
+    }
+
+    fn part1(input: String) -> u32 {
+        input
+            .lines()
+            .map(|line| line.split_at(line.len() / 2))
+            .map(|(xx, yy)| intersect(&[xx, yy]))
+            .map(priority)
+            .sum()
+    }
+
+    fn part2(input: String) -> u32 {
+        input
+            .lines()
+            .collect::<Vec<_>>()
+            .chunks(3)
+            .map(intersect)
+            .map(priority)
+            .sum()
+    }
 }
 
 fn priority(c: char) -> u32 {
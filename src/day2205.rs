@@ -1,68 +1,274 @@
 use crate::util::*;
 
-aoc_test!(part1, 220501, "CMZ".to_string());
-aoc_test!(part1, 220500, "PTWLTDSJV".to_string());
-aoc_test!(part2, 220501, "MCD".to_string());
-aoc_test!(part2, 220500, "WZMFVGGZP".to_string());
+aoc_register!(Day2205);
+aoc_test!(Day2205);
 
+/// The stacks of crates, each from the bottom up
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stacks(Vec<Vec<char>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Move {
     n: usize,
     src: usize,
     dst: usize,
+    /// The line in the input the move is on, for error messages
+    line: usize,
+}
+
+/// The starting stacks, and the moves the crane operator is asked to make
+#[derive(Debug, Clone)]
+struct Procedure {
+    stacks: Stacks,
+    moves: Vec<Move>,
+}
+
+/// A crane takes a number of crates off one stack and puts them on another.
+/// The only thing cranes differ in is what that does to the order of the
+/// crates.
+trait Crane {
+    /// Rearrange the crates picked up in a move, given from the bottom up,
+    /// into the order in which they end up on the destination stack
+    fn carry(&self, crates: &mut [char]);
+}
+
+/// Moves one crate at a time, which reverses their order
+struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn carry(&self, crates: &mut [char]) {
+        crates.reverse();
+    }
+}
+
+/// Moves all crates at once, which keeps them in order
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn carry(&self, _crates: &mut [char]) {}
 }
 
-fn part1(input: String) -> String {
-    let input = input.as_str();
-    let mut piles = parse_piles(input);
-    for Move { n, src, dst } in parse_moves(input) {
-        for _ in 0..n {
-            if let Some(c) = piles[src - 1].pop() {
-                piles[dst - 1].push(c);
+/// Any other crane can be made out of a function
+impl<F: Fn(&mut [char])> Crane for F {
+    fn carry(&self, crates: &mut [char]) {
+        self(crates)
+    }
+}
+
+struct Day2205;
+
+impl Solution for Day2205 {
+    type Input = Procedure;
+    type Output1 = String;
+    type Output2 = String;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        5
+    }
+
+    fn part1(procedure: Procedure) -> String {
+        let stacks = procedure.run(&CrateMover9000, usize::MAX);
+        stacks.expect("invalid move").tops()
+    }
+
+    fn part2(procedure: Procedure) -> String {
+        let stacks = procedure.run(&CrateMover9001, usize::MAX);
+        stacks.expect("invalid move").tops()
+    }
+}
+
+impl Procedure {
+    /// The stacks after the first `steps` moves
+    fn run(&self, crane: &impl Crane, steps: usize) -> anyhow::Result<Stacks> {
+        let mut stacks = self.stacks.clone();
+        for m in self.moves.iter().take(steps) {
+            stacks.apply(m, crane)?;
+        }
+        Ok(stacks)
+    }
+}
+
+impl Stacks {
+    /// The crate on top of each stack
+    fn tops(&self) -> String {
+        self.0.iter().flat_map(|stack| stack.last()).collect()
+    }
+
+    fn apply(&mut self, m: &Move, crane: &impl Crane) -> anyhow::Result<()> {
+        let Move { n, src, dst, line } = *m;
+        let fail = |why: String| anyhow!("line {line}: move {n} from {src} to {dst}: {why}");
+        for i in [src, dst] {
+            if !(1..=self.0.len()).contains(&i) {
+                return Err(fail(format!("there is no stack {i}")));
             }
         }
+        let stack = &mut self.0[src - 1];
+        let Some(split) = stack.len().checked_sub(n) else {
+            let len = stack.len();
+            return Err(fail(format!("stack {src} only has {len} crates")));
+        };
+        let mut crates = stack.split_off(split);
+        crane.carry(&mut crates);
+        self.0[dst - 1].append(&mut crates);
+        Ok(())
+    }
+}
+
+/// Draws the stacks the way the puzzle does
+impl Display for Stacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        for row in (0..height).rev() {
+            let mut crates = self.0.iter().map(|stack| match stack.get(row) {
+                Some(c) => format!("[{c}]"),
+                None => "   ".to_string(),
+            });
+            writeln!(f, "{}", crates.join(" "))?;
+        }
+        let mut labels = (1..=self.0.len()).map(|i| format!(" {i} "));
+        writeln!(f, "{}", labels.join(" "))
     }
-    get_tops(piles)
 }
 
-fn part2(input: String) -> String {
-    let input = input.as_str();
-    let mut piles = parse_piles(input);
-    for Move { n, src, dst } in parse_moves(input) {
-        let split_idx = piles[src - 1].len() - n;
-        let mut stack = piles[src - 1].split_off(split_idx);
-        piles[dst - 1].append(&mut stack);
+/// Parses a drawing of the stacks. Crates have to sit right above the label
+/// of their stack, and on top of another crate or the floor.
+impl FromStr for Stacks {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Columns line up by position, so stick to one byte per character
+        if let Some(c) = s.chars().find(|c| !c.is_ascii()) {
+            return Err(anyhow!("unexpected {c:?} in the drawing"));
+        }
+        let lines = s
+            .trim_end_matches('\n')
+            .lines()
+            .map(str::as_bytes)
+            .collect_vec();
+        let (labels, rows) = lines.split_last().ok_or(anyhow!("no stacks"))?;
+        let columns = (0..labels.len())
+            .filter(|&col| labels[col] != b' ' && (col == 0 || labels[col - 1] == b' '))
+            .collect_vec();
+        let words = labels.split(|&b| b == b' ').filter(|word| !word.is_empty());
+        for (i, word) in (1..).zip(words) {
+            let word = String::from_utf8_lossy(word);
+            if word != i.to_string() {
+                return Err(anyhow!("expected label {i}, but found {word:?}"));
+            }
+        }
+        let mut stacks = vec![vec![]; columns.len()];
+        for (row, line) in rows.iter().rev().enumerate() {
+            let at = |col: usize| line.get(col).copied().unwrap_or(b' ') as char;
+            let mut col = 0;
+            while col < line.len() {
+                match at(col) {
+                    ' ' => col += 1,
+                    '[' if at(col + 2) == ']' => {
+                        let stack = columns
+                            .iter()
+                            .position(|&c| c == col + 1)
+                            .ok_or(anyhow!("crate at column {} isn't on a stack", col + 2))?;
+                        if stacks[stack].len() != row {
+                            return Err(anyhow!(
+                                "crate {} on stack {} floats",
+                                at(col + 1),
+                                stack + 1
+                            ));
+                        }
+                        stacks[stack].push(at(col + 1));
+                        col += 3;
+                    }
+                    c => return Err(anyhow!("unexpected {c:?} at column {}", col + 1)),
+                }
+            }
+        }
+        Ok(Stacks(stacks))
     }
-    get_tops(piles)
 }
 
-fn get_tops(piles: Vec<Vec<char>>) -> String {
-    piles.into_iter().flat_map(|mut pile| pile.pop()).join("")
+impl FromStr for Procedure {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (drawing, moves) = s.split_once("\n\n").ok_or(anyhow!(
+            "expected a drawing and moves, separated by an empty line"
+        ))?;
+        let stacks = drawing.parse()?;
+        let first = drawing.lines().count() + 2;
+        let moves = (first..)
+            .zip(moves.lines())
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(line, text)| {
+                let words = text.split_whitespace().collect_vec();
+                let number = |s: &str| {
+                    s.parse()
+                        .map_err(|_| anyhow!("line {line}: bad number {s:?}"))
+                };
+                match words.as_slice() {
+                    ["move", n, "from", src, "to", dst] => Ok(Move {
+                        n: number(n)?,
+                        src: number(src)?,
+                        dst: number(dst)?,
+                        line,
+                    }),
+                    _ => Err(anyhow!("line {line}: can't make sense of {text:?}")),
+                }
+            })
+            .try_collect()?;
+        Ok(Procedure { stacks, moves })
+    }
 }
 
-fn parse_moves(input: &str) -> impl Iterator<Item = Move> + '_ {
-    input
-        .lines()
-        .filter(|line| line.starts_with("move"))
-        .flat_map(|line| {
-            line.split_whitespace()
-                .dropping(1)
-                .step_by(2)
-                .flat_map(|s| s.parse::<usize>())
-                .collect_tuple::<(usize, usize, usize)>()
-        })
-        .map(|(n, src, dst)| Move { n, src, dst })
+#[test]
+fn test_cranes() {
+    let procedure: Procedure = parse_test_file(220501);
+    let drawing = load_input::<String>(220501).unwrap();
+    let drawing = drawing.split_once("\n\n").unwrap().0;
+    assert_eq!(procedure.stacks.to_string(), format!("{drawing}\n"));
+    let after = procedure.run(&CrateMover9000, 1).unwrap();
+    assert_eq!(
+        after.to_string(),
+        "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n"
+    );
+    // A crane that drops everything upside down, except for the bottom crate
+    let clumsy = |crates: &mut [char]| {
+        if let Some((_, rest)) = crates.split_first_mut() {
+            rest.reverse()
+        }
+    };
+    assert_eq!(procedure.run(&clumsy, usize::MAX).unwrap().tops(), "MCN");
 }
 
-fn parse_piles(input: &str) -> Vec<Vec<char>> {
-    input
-        .lines()
-        .filter(|line| line.trim_start().starts_with('['))
-        .rev()
-        .flat_map(|line| line.chars().dropping(1).step_by(4).enumerate())
-        .filter(|(_, c)| c != &' ')
-        .sorted_by_key(|(i, _)| *i)
-        .group_by(|(i, _)| *i)
-        .into_iter()
-        .map(|(_, group)| group.map(|(_, c)| c).collect_vec())
-        .collect_vec()
+#[test]
+fn test_errors() {
+    let error = |s: &str| s.parse::<Procedure>().unwrap_err().to_string();
+    assert_eq!(
+        error("[A]\n 1   3\n\n"),
+        "expected label 2, but found \"3\""
+    );
+    assert_eq!(
+        error("[A]\n    [B]\n 1   2\n\n"),
+        "crate A on stack 1 floats"
+    );
+    assert_eq!(error(" [A]\n 1\n\n"), "crate at column 3 isn't on a stack");
+    assert_eq!(error("[A] x\n 1\n\n"), "unexpected 'x' at column 5");
+    assert_eq!(
+        error("[é] [A]\n 1   2\n\n"),
+        "unexpected 'é' in the drawing"
+    );
+    assert_eq!(
+        error("[A]\n 1 \n\nmove 1 from 1 to 2\nmove one from 1 to 1"),
+        "line 5: bad number \"one\""
+    );
+    let procedure: Procedure = "[A]\n 1   2 \n\nmove 1 from 1 to 2\nmove 2 from 2 to 1"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        procedure.run(&CrateMover9000, 2).unwrap_err().to_string(),
+        "line 5: move 2 from 2 to 1: stack 2 only has 1 crates"
+    );
 }
@@ -1,26 +1,113 @@
+use std::io::{BufReader, Read};
+
 use crate::util::*;
+use test_case::test_case;
 
-aoc_test!(part1, 220601, 7);
-aoc_test!(part1, 220600, 1361);
-aoc_test!(part2, 220601, 19);
-aoc_test!(part2, 220600, 3263);
+aoc_register!(Day2206);
+aoc_test!(Day2206);
 
-fn part1(input: String) -> usize {
-    find_marker_of_size(input, 4)
-}
+struct Day2206;
+
+impl Solution for Day2206 {
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        6
+    }
 
-fn part2(input: String) -> usize {
-    find_marker_of_size(input, 14)
+    fn part1(input: String) -> usize {
+        find_marker_of_size(input, 4)
+    }
+
+    fn part2(input: String) -> usize {
+        find_marker_of_size(input, 14)
+    }
 }
 
 fn find_marker_of_size(input: String, n: usize) -> usize {
-    use std::collections::HashSet;
-    input
-        .chars()
-        .collect_vec()
-        .windows(n)
-        .find_position(|window| window.iter().collect::<HashSet<_>>().len() == n)
+    markers(input.as_bytes(), n)
+        .next()
         .expect("not found")
-        .0
-        + n
+        .expect("can't read input")
+}
+
+/// Every marker of the given size in a stream: the number of bytes read so
+/// far, whenever the last `size` of them are all different
+fn markers<R: Read>(reader: R, size: usize) -> Markers<BufReader<R>> {
+    assert!(size > 0, "markers can't be empty");
+    Markers {
+        bytes: BufReader::new(reader).bytes(),
+        window: vec![0; size],
+        counts: [0; 256],
+        distinct: 0,
+        pos: 0,
+    }
+}
+
+/// Slides a window over the stream, keeping count of how often each byte
+/// occurs in it, so that every byte takes constant time, whatever the size
+/// of the window.
+struct Markers<R> {
+    bytes: std::io::Bytes<R>,
+    /// The last bytes read, as a ring buffer
+    window: Vec<u8>,
+    counts: [usize; 256],
+    /// The number of different bytes in the window
+    distinct: usize,
+    /// The number of bytes read so far
+    pos: usize,
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = std::io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let size = self.window.len();
+        loop {
+            let byte = match self.bytes.next()? {
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(e)),
+            };
+            let slot = &mut self.window[self.pos % size];
+            if self.pos >= size {
+                self.counts[*slot as usize] -= 1;
+                if self.counts[*slot as usize] == 0 {
+                    self.distinct -= 1;
+                }
+            }
+            *slot = byte;
+            if self.counts[byte as usize] == 0 {
+                self.distinct += 1;
+            }
+            self.counts[byte as usize] += 1;
+            self.pos += 1;
+            if self.distinct == size {
+                return Some(Ok(self.pos));
+            }
+        }
+    }
+}
+
+#[test_case("bvwbjplbgvbhsrlpgdmjqwftvncz", 4, 5)]
+#[test_case("nppdvjthqldpwncqszvftbrmjlhg", 4, 6)]
+#[test_case("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14, 29)]
+#[test_case("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14, 26)]
+fn test_first_marker(input: &str, size: usize, marker: usize) {
+    assert_eq!(find_marker_of_size(input.to_string(), size), marker);
+}
+
+#[test]
+fn test_all_markers() {
+    let all = |input: &[u8], size| markers(input, size).map(Result::unwrap).collect_vec();
+    assert_eq!(all(b"abcabcc", 3), [3, 4, 5, 6]);
+    assert_eq!(all(b"aaa", 1), [1, 2, 3]);
+    assert!(all(b"abab", 3).is_empty());
+    let long = std::io::repeat(b'a').take(1 << 24).chain(&b"bcd"[..]);
+    assert_eq!(markers(long, 4).next().unwrap().unwrap(), (1 << 24) + 3);
 }
@@ -1,62 +1,356 @@
+use std::collections::BTreeMap;
+
 use crate::util::*;
 
-aoc_test!(part1, 220701, 95437);
-aoc_test!(part1, 220700, 1555642);
-aoc_test!(part2, 220701, 24933642);
-aoc_test!(part2, 220700, 5974547);
-
-fn part1(input: String) -> usize {
-    let size_by_name = get_size_by_name(input);
-    size_by_name.values().filter(|v| v <= &&100000).sum()
-}
-
-fn part2(input: String) -> usize {
-    let size_by_name = get_size_by_name(input);
-    let disk_size = 70000000_usize;
-    let used = *size_by_name.get("").unwrap_or(&0);
-    let free = disk_size - used;
-    let required = 30000000;
-    let overage = required - free;
-    size_by_name
-        .into_values()
-        .sorted()
-        .find(|v| *v >= overage)
-        .unwrap_or(0)
-}
-
-fn get_size_by_name(input: String) -> HashMap<String, usize> {
-    let mut cwd: Vec<String> = std::iter::empty().collect();
-    let mut size_by_name: HashMap<String, usize> = std::iter::empty().collect();
-    let lines = input
-        .lines()
-        .map(|line| line.split_ascii_whitespace().collect_vec());
-    for line in lines {
-        match line.as_slice() {
-            ["$", "ls"] => {}
-            ["$", "cd", ".."] => {
-                cwd.pop();
-            }
-            ["$", "cd", "/"] => {
-                cwd.clear();
-                cwd.push("".to_string())
-            }
-            ["$", "cd", name] => {
-                let path = format!(
-                    "{}/{}",
-                    cwd.last().cloned().unwrap_or_else(String::new),
-                    name
-                );
-                cwd.push(path);
-            }
-            ["dir", _] => {}
-            [size, _] => {
-                let size: usize = size.parse().unwrap_or(0);
-                for component in cwd.iter() {
-                    *size_by_name.entry(component.to_string()).or_insert(0) += size;
+aoc_register!(Day2207);
+aoc_test!(Day2207);
+
+struct Day2207;
+
+impl Solution for Day2207 {
+    type Input = Filesystem;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        7
+    }
+
+    fn part1(fs: Filesystem) -> usize {
+        fs.du()
+            .into_iter()
+            .map(|(_, size)| size)
+            .filter(|&size| size <= 100000)
+            .sum()
+    }
+
+    fn part2(fs: Filesystem) -> usize {
+        let needed = fs
+            .needed(70000000, 30000000)
+            .expect("impossible filesystem");
+        fs.smallest_to_free(needed).map_or(0, |(_, size)| size)
+    }
+}
+
+/// A directory tree, as far as we got to see it in a terminal session. The
+/// directories live in a `Vec`, and refer to each other by index; the root is
+/// the first one.
+#[derive(Debug, Clone)]
+struct Filesystem {
+    dirs: Vec<Dir>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Dir {
+    name: String,
+    parent: Option<usize>,
+    entries: BTreeMap<String, Entry>,
+    /// Whether we've seen an `ls` of this directory yet
+    listed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Dir(usize),
+    File(usize),
+}
+
+impl Filesystem {
+    const ROOT: usize = 0;
+
+    fn new() -> Self {
+        let root = Dir {
+            name: "/".to_string(),
+            ..Dir::default()
+        };
+        Filesystem { dirs: vec![root] }
+    }
+
+    /// The full path of a directory, like `/a/e`
+    fn path(&self, dir: usize) -> String {
+        let names = std::iter::successors(Some(dir), |&dir| self.dirs[dir].parent)
+            .filter(|&dir| dir != Self::ROOT)
+            .map(|dir| self.dirs[dir].name.as_str())
+            .collect_vec();
+        format!("/{}", names.into_iter().rev().join("/"))
+    }
+
+    /// The total size of all files in a directory and its subdirectories
+    fn size(&self, dir: usize) -> usize {
+        self.sizes()[dir]
+    }
+
+    fn sizes(&self) -> Vec<usize> {
+        // Directories are always created after their parent, so going
+        // backwards, we get to every directory after all of its children.
+        let mut sizes = vec![0; self.dirs.len()];
+        for (i, dir) in self.dirs.iter().enumerate().rev() {
+            for entry in dir.entries.values() {
+                sizes[i] += match *entry {
+                    Entry::Dir(child) => sizes[child],
+                    Entry::File(size) => size,
+                };
+            }
+        }
+        sizes
+    }
+
+    /// Every directory along with its total size, like `du` prints them:
+    /// children before their parents
+    fn du(&self) -> Vec<(String, usize)> {
+        let sizes = self.sizes();
+        let mut out = vec![];
+        self.walk(Self::ROOT, &mut |dir| {
+            out.push((self.path(dir), sizes[dir]))
+        });
+        out
+    }
+
+    fn walk(&self, dir: usize, visit: &mut impl FnMut(usize)) {
+        for entry in self.dirs[dir].entries.values() {
+            if let Entry::Dir(child) = *entry {
+                self.walk(child, visit);
+            }
+        }
+        visit(dir);
+    }
+
+    /// How much space we need to free up to have `required` bytes free on a
+    /// disk that holds `disk_size` bytes
+    fn needed(&self, disk_size: usize, required: usize) -> anyhow::Result<usize> {
+        let used = self.size(Self::ROOT);
+        let free = disk_size.checked_sub(used).ok_or_else(|| {
+            anyhow!("the files take up {used} bytes, but the disk only holds {disk_size}")
+        })?;
+        Ok(required.saturating_sub(free))
+    }
+
+    /// The smallest directory that frees up at least `needed` bytes when
+    /// deleted
+    fn smallest_to_free(&self, needed: usize) -> Option<(String, usize)> {
+        self.du()
+            .into_iter()
+            .filter(|&(_, size)| size >= needed)
+            .min_by_key(|&(_, size)| size)
+    }
+
+    fn fmt_dir(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        dir: usize,
+        depth: usize,
+    ) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(f, "{indent}- {} (dir)", self.dirs[dir].name)?;
+        for (name, entry) in self.dirs[dir].entries.iter() {
+            match *entry {
+                Entry::Dir(child) => self.fmt_dir(f, child, depth + 1)?,
+                Entry::File(size) => writeln!(f, "{indent}  - {name} (file, size={size})")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints the tree like the puzzle does, with entries sorted by name
+impl Display for Filesystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_dir(f, Self::ROOT, 0)
+    }
+}
+
+/// The `ls` whose output we're reading
+struct Listing {
+    dir: usize,
+    /// Whether `dir` was listed before, in which case this listing has to
+    /// mention exactly the same entries
+    relisted: bool,
+    seen: HashSet<String>,
+}
+
+/// Replay a terminal session, building up the tree from the output of `ls`.
+/// Listing a directory twice is fine, as long as both listings agree.
+impl FromStr for Filesystem {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fs = Filesystem::new();
+        let mut cwd = Self::ROOT;
+        let mut listing: Option<Listing> = None;
+        for (i, line) in s.lines().enumerate() {
+            let fail = |what: String| anyhow!("line {}: {what}", i + 1);
+            let words = line.split_ascii_whitespace().collect_vec();
+            if words.first() == Some(&"$") {
+                if let Some(listing) = listing.take() {
+                    fs.finish(listing).map_err(fail)?;
                 }
             }
-            _ => {}
+            match words.as_slice() {
+                ["$", "ls"] => {
+                    let dir = &mut fs.dirs[cwd];
+                    listing = Some(Listing {
+                        dir: cwd,
+                        relisted: dir.listed,
+                        seen: HashSet::new(),
+                    });
+                    dir.listed = true;
+                }
+                ["$", "cd", "/"] => cwd = Self::ROOT,
+                ["$", "cd", ".."] => {
+                    cwd = fs.dirs[cwd]
+                        .parent
+                        .ok_or_else(|| fail("cd .. from the root".to_string()))?;
+                }
+                ["$", "cd", name] => match fs.dirs[cwd].entries.get(*name) {
+                    Some(Entry::Dir(child)) => cwd = *child,
+                    Some(Entry::File(_)) => Err(fail(format!("cd into file {name}")))?,
+                    None => Err(fail(format!(
+                        "cd into {name}, which was never listed in {}",
+                        fs.path(cwd)
+                    )))?,
+                },
+                [kind, name] if *kind != "$" => {
+                    let listing = listing
+                        .as_mut()
+                        .ok_or_else(|| fail("output without an ls".to_string()))?;
+                    let entry = match *kind {
+                        "dir" => None,
+                        size => Some(Entry::File(
+                            size.parse()
+                                .map_err(|_| fail(format!("bad file size {size:?}")))?,
+                        )),
+                    };
+                    fs.add(listing, name, entry).map_err(fail)?;
+                }
+                _ => Err(fail(format!("can't make sense of {line:?}")))?,
+            }
+        }
+        if let Some(listing) = listing {
+            fs.finish(listing)
+                .map_err(|e| anyhow!("at the end of the session: {e}"))?;
         }
+        Ok(fs)
     }
-    size_by_name
+}
+
+impl Filesystem {
+    /// Add an entry from a listing: a file, or a directory if `entry` is
+    /// `None`
+    fn add(
+        &mut self,
+        listing: &mut Listing,
+        name: &str,
+        entry: Option<Entry>,
+    ) -> Result<(), String> {
+        let dir = listing.dir;
+        let path = self.path(dir);
+        if !listing.seen.insert(name.to_string()) {
+            return Err(format!("{name} is listed twice in {path}"));
+        }
+        let old = self.dirs[dir].entries.get(name).copied();
+        match (old, entry) {
+            (None, _) if listing.relisted => {
+                Err(format!("{name} is new in a second listing of {path}"))
+            }
+            (None, Some(file)) => {
+                self.dirs[dir].entries.insert(name.to_string(), file);
+                Ok(())
+            }
+            (None, None) => {
+                let child = Dir {
+                    name: name.to_string(),
+                    parent: Some(dir),
+                    ..Dir::default()
+                };
+                let entry = Entry::Dir(self.dirs.len());
+                self.dirs.push(child);
+                self.dirs[dir].entries.insert(name.to_string(), entry);
+                Ok(())
+            }
+            (Some(Entry::Dir(_)), None) => Ok(()),
+            (Some(old), Some(new)) if old == new => Ok(()),
+            _ => Err(format!("{name} changed between listings of {path}")),
+        }
+    }
+
+    /// A second listing can't add anything (`add` sees to that), but it
+    /// could still leave things out
+    fn finish(&self, listing: Listing) -> Result<(), String> {
+        let entries = &self.dirs[listing.dir].entries;
+        match entries.keys().find(|name| !listing.seen.contains(*name)) {
+            Some(name) if listing.relisted => Err(format!(
+                "{name} is missing from a second listing of {}",
+                self.path(listing.dir)
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_tree() {
+    let fs: Filesystem = parse_test_file(220701);
+    let tree = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+    assert_eq!(fs.to_string(), tree);
+    let du = [
+        ("/a/e", 584),
+        ("/a", 94853),
+        ("/d", 24933642),
+        ("/", 48381165),
+    ];
+    let du = du.map(|(path, size)| (path.to_string(), size));
+    assert_eq!(fs.du(), du);
+    assert_eq!(fs.smallest_to_free(90000), Some(("/a".to_string(), 94853)));
+    assert_eq!(fs.smallest_to_free(50000000), None);
+    assert_eq!(fs.needed(70000000, 30000000).unwrap(), 8381165);
+    assert_eq!(fs.needed(50000000, 1000).unwrap(), 0);
+    assert_eq!(
+        fs.needed(40000000, 1000).unwrap_err().to_string(),
+        "the files take up 48381165 bytes, but the disk only holds 40000000"
+    );
+}
+
+#[test]
+fn test_replay() {
+    let session = |lines: &[&str]| lines.join("\n").parse::<Filesystem>();
+    let twice = session(&[
+        "$ ls", "dir a", "5 b", "$ cd a", "$ cd ..", "$ ls", "5 b", "dir a",
+    ]);
+    assert_eq!(twice.unwrap().size(Filesystem::ROOT), 5);
+    let error = |lines: &[&str]| session(lines).unwrap_err().to_string();
+    assert_eq!(
+        error(&["$ ls", "dir a", "$ cd b"]),
+        "line 3: cd into b, which was never listed in /"
+    );
+    assert_eq!(
+        error(&["$ ls", "5 b", "$ ls", "6 b"]),
+        "line 4: b changed between listings of /"
+    );
+    assert_eq!(
+        error(&["$ ls", "5 b", "dir a", "$ ls", "dir a"]),
+        "at the end of the session: b is missing from a second listing of /"
+    );
+    assert_eq!(
+        error(&["$ cd /", "$ rm -rf"]),
+        "line 2: can't make sense of \"$ rm -rf\""
+    );
 }
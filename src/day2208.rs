@@ -1,133 +1,141 @@
+use ndarray::Array2;
+
 use crate::util::*;
 
-use ndarray::Array2;
+use aoc_grid::*;
 
-aoc_test!(part1, 220801, 21);
-aoc_test!(part1, 220800, 1719);
-aoc_test!(part2, 220801, 8);
-aoc_test!(part2, 220800, 590824);
+aoc_register!(Day2208);
+aoc_test!(Day2208);
 
-type Pos = (usize, usize);
 type Height = i32;
 
 struct Map {
-    heights: Array2<Height>,
+    heights: Grid<Height>,
 }
 
 impl FromStr for Map {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let height = |c| (u32::from(c) - u32::from('0')) as Height;
-        let heights = s.lines().map(|line| line.chars().map(height));
-        let heights = aoc::array2(heights)?;
+        let height = |c: char| {
+            c.to_digit(10)
+                .map(|h| h as Height)
+                .ok_or(anyhow!("not a digit"))
+        };
+        let heights = Grid::parse(s, height)?;
         Ok(Map { heights })
     }
 }
 
-#[derive(Clone, Copy, EnumIter, Debug)]
-enum Dir {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
-fn part1(map: Map) -> usize {
-    map.n_visible_from_edge()
-}
+struct Day2208;
 
-fn part2(map: Map) -> usize {
-    map.max_scenic_score()
-}
+impl Solution for Day2208 {
+    type Input = Map;
+    type Output1 = usize;
+    type Output2 = usize;
 
-impl Map {
-    /// The maximum scenic score is the highest of the scenic scores of all trees
-    fn max_scenic_score(&self) -> usize {
-        self.heights
-            .indexed_iter()
-            .map(|(pos, height)| self.scenic_score(pos, *height))
-            .max()
-            .unwrap_or(0)
+    fn year() -> u16 {
+        2022
     }
 
-    /// The scenic score for a tree is the product of its viewing distances in all 4 directions
-    fn scenic_score(&self, pos: Pos, height: Height) -> usize {
-        Dir::iter()
-            .map(|dir| self.viewing_distance(pos, height, dir))
-            .product()
+    fn day() -> u8 {
+        8
     }
 
-    /// The viewing distance from some tree in some direction is the length of its view
-    fn viewing_distance(&self, pos: Pos, height: Height, dir: Dir) -> usize {
-        self.view(pos, height, dir).count()
+    fn part1(map: Map) -> usize {
+        map.n_visible_from_edge()
     }
 
-    /// To find the view from a given tree in a given direction, keep walking until we hit a tree
-    /// that is at least as tall as the first, or the edge
-    fn view(&self, pos: Pos, height: Height, dir: Dir) -> impl Iterator<Item = Pos> + '_ {
-        self.path(pos, height, dir)
-            .skip(1)
-            .take_while_inclusive(move |&(_, h)| h < height)
-            .map(|(pos, _)| pos)
+    fn part2(map: Map) -> usize {
+        map.max_scenic_score()
     }
+}
 
-    /// From a given starting position, the path in some direction is the sequence of heights of
-    /// the trees you pass moving in that direction
-    fn path(&self, pos: Pos, height: Height, dir: Dir) -> impl Iterator<Item = (Pos, Height)> + '_ {
-        let first = std::iter::once((pos, height));
-        let rest = std::iter::repeat(()).scan(pos, move |cur, _| {
-            if let Some(nxt) = Self::next(dir, *cur) {
-                *cur = nxt;
-                self.heights.get(nxt).map(|h| (nxt, *h))
-            } else {
-                None
-            }
-        });
-        first.chain(rest)
+/// What every tree in the forest sees, worked out for all trees at once
+struct Survey {
+    /// Whether a tree can be seen from outside the forest
+    visible: Array2<bool>,
+    /// How many trees a tree can see in each direction, in the order of
+    /// `Dir::ALL`
+    distances: [Array2<usize>; 4],
+    /// The product of the viewing distances in all directions
+    scenic: Array2<usize>,
+}
+
+impl Map {
+    fn max_scenic_score(&self) -> usize {
+        self.survey().scenic.into_iter().max().unwrap_or(0)
     }
 
-    /// For a given starting position, compute the next position in some direction
-    fn next(dir: Dir, (row, col): Pos) -> Option<Pos> {
-        if let (Some(row), Some(col)) = match dir {
-            Dir::Left => (Some(row), col.checked_sub(1)),
-            Dir::Right => (Some(row), col.checked_add(1)),
-            Dir::Up => (row.checked_sub(1), Some(col)),
-            Dir::Down => (row.checked_add(1), Some(col)),
-        } {
-            Some((row, col))
-        } else {
-            None
-        }
+    fn n_visible_from_edge(&self) -> usize {
+        self.survey().visible.into_iter().filter(|&v| v).count()
     }
 
-    /// To find the view from an edge position in a given direction, walk all the way to the other
-    /// end, reporting every tree that is taller than the tallest seen before it
-    fn edge_view(&self, fst: Pos, dir: Dir) -> impl Iterator<Item = Pos> + '_ {
-        let height = self.heights[fst];
-        let view = self.path(fst, height, dir);
-        view.scan(-1, |tallest, (pos, h)| {
-            if h > *tallest {
-                *tallest = h;
-                Some(Some(pos))
-            } else {
-                Some(None)
+    /// Walk every row and column from both ends, keeping a stack of the trees
+    /// that block the view back towards the edge we started from, tallest at
+    /// the bottom. Every tree is pushed and popped at most once per walk, so
+    /// that takes linear time overall.
+    fn survey(&self) -> Survey {
+        let shape = self.heights.dim();
+        let mut visible = Array2::from_elem(shape, false);
+        let mut distances: [_; 4] = std::array::from_fn(|_| Array2::zeros(shape));
+        for (start, inward) in self.heights.edges() {
+            let look = Dir::ALL
+                .iter()
+                .position(|&d| d == inward.opposite())
+                .unwrap();
+            let line = std::iter::once(start).chain(self.heights.ray(start, inward));
+            let mut stack: Vec<(usize, Height)> = vec![];
+            for (i, pos) in line.enumerate() {
+                let height = self.heights[pos];
+                while stack.last().is_some_and(|&(_, h)| h < height) {
+                    stack.pop();
+                }
+                distances[look][<(usize, usize)>::from(pos)] = match stack.last() {
+                    Some(&(j, _)) => i - j,
+                    None => {
+                        visible[<(usize, usize)>::from(pos)] = true;
+                        i
+                    }
+                };
+                stack.push((i, height));
             }
-        })
-        .flatten()
+        }
+        let scenic = distances
+            .iter()
+            .fold(Array2::ones(shape), |scenic, distance| scenic * distance);
+        Survey {
+            visible,
+            distances,
+            scenic,
+        }
     }
+}
 
-    /// To count the number of trees that are visible from some edge, we gather up all the
-    /// `edge_view`s and count the number of distinct trees they contain
-    fn n_visible_from_edge(&self) -> usize {
-        let (rows, cols) = self.heights.dim();
-        itertools::chain!(
-            (0..rows).flat_map(|row| self.edge_view((row, 0), Dir::Right)),
-            (0..rows).flat_map(|row| self.edge_view((row, cols - 1), Dir::Left)),
-            (0..cols).flat_map(|col| self.edge_view((0, col), Dir::Down)),
-            (0..cols).flat_map(|col| self.edge_view((rows - 1, col), Dir::Up))
-        )
-        .unique()
-        .count()
-    }
+/// Render a field of numbers with characters that get denser as the
+/// numbers get bigger
+fn heatmap(field: &Array2<usize>) -> String {
+    const RAMP: &[u8] = b" .:-=+*#%@";
+    let max = field.iter().copied().max().unwrap_or(0).max(1);
+    let heat = |&v: &usize| RAMP[v * (RAMP.len() - 1) / max] as char;
+    field
+        .rows()
+        .into_iter()
+        .map(|row| row.iter().map(heat).collect::<String>())
+        .join("\n")
+}
+
+#[test]
+fn test_survey() {
+    let map: Map = parse_test_file(220801);
+    let survey = map.survey();
+    let visible = survey.visible.map(|&v| if v { '#' } else { '.' });
+    let visible = Grid::from(visible).to_string();
+    assert_eq!(visible, "#####\n###.#\n##.##\n#.#.#\n#####\n");
+    assert_eq!(survey.scenic[(1, 2)], 4);
+    assert_eq!(survey.scenic[(3, 2)], 8);
+    let up = Dir::ALL.iter().position(|&d| d == Dir::N).unwrap();
+    assert_eq!(survey.distances[up][(3, 2)], 2);
+    let heatmap = heatmap(&survey.scenic);
+    assert_eq!(heatmap.lines().nth(3).unwrap(), " .@- ");
 }
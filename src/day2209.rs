@@ -1,19 +1,11 @@
 use crate::util::*;
 
-aoc_test!(part1, 220901, 13);
-aoc_test!(part1, 220900, 6212);
-// aoc_parse_and_test!(part2, 220901, 1);
-// aoc_parse_and_test!(part2, 220900, -1);
-
-struct Move(Direction, u32);
-
-#[derive(Debug)]
-enum Direction {
-    R,
-    L,
-    U,
-    D,
-}
+use aoc_grid::*;
+
+aoc_register!(Day2209);
+aoc_test!(Day2209);
+
+struct Move(Dir, u32);
 
 impl FromStr for Move {
     type Err = anyhow::Error;
@@ -25,61 +17,132 @@ impl FromStr for Move {
             .map(|(dir, dist)| {
                 let dist = dist.parse().unwrap_or(0);
                 match dir {
-                    "R" => Move(Direction::R, dist),
-                    "L" => Move(Direction::L, dist),
-                    "U" => Move(Direction::U, dist),
-                    _ => Move(Direction::D, dist),
+                    "R" => Move(Dir::E, dist),
+                    "L" => Move(Dir::W, dist),
+                    "U" => Move(Dir::N, dist),
+                    _ => Move(Dir::S, dist),
                 }
             })
             .ok_or(anyhow!("oops"))
     }
 }
 
-fn walk(x: &mut (i32, i32), dx: (i32, i32)) {
-    x.0 += dx.0;
-    x.1 += dx.1;
+struct Day2209;
+
+impl Solution for Day2209 {
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        9
+    }
+
+    fn part1(input: String) -> usize {
+        let mut rope = Rope::new(2);
+        rope.perform(parse(&input));
+        rope.visited_by_tail()
+    }
+
+    fn part2(input: String) -> usize {
+        let mut rope = Rope::new(10);
+        rope.perform(parse(&input));
+        rope.visited_by_tail()
+    }
+}
+
+/// A rope made of knots, the first of which is the head. Every knot follows
+/// the one before it, and we keep track of every position each knot visits.
+struct Rope {
+    knots: Vec<Point>,
+    visited: Vec<HashSet<Point>>,
 }
 
-fn part1(input: String) -> usize {
-    let input = input.as_str();
-    let mut tail_positions = HashSet::new();
-    let mut head_position = (0, 0);
-    let mut tail_position = (0, 0);
-    for Move(direction, dist) in parse(input) {
-        for _ in 0..dist {
-            lead(&mut head_position, &direction);
-            follow(&mut tail_position, &head_position);
-            tail_positions.insert(tail_position);
+impl Rope {
+    fn new(length: usize) -> Self {
+        assert!(length > 0, "a rope needs at least a head");
+        Rope {
+            knots: vec![Point::ORIGIN; length],
+            visited: vec![HashSet::from([Point::ORIGIN]); length],
         }
     }
-    tail_positions.len()
-}
 
-fn follow(follower: &mut (i32, i32), leader: &(i32, i32)) {
-    let dx = leader.0 - follower.0;
-    let dy = leader.1 - follower.1;
-    let d_tail = match (dx.abs(), dy.abs()) {
-        (0, 2) | (2, 0) | (1, 2) | (2, 1) => (dx.signum(), dy.signum()),
-        _ => (0, 0),
-    };
-    walk(follower, d_tail);
+    /// Perform a series of moves, one step at a time
+    fn perform(&mut self, moves: impl IntoIterator<Item = Move>) {
+        for Move(direction, dist) in moves {
+            for _ in 0..dist {
+                self.step(direction);
+            }
+        }
+    }
+
+    /// Move the head one step, and let all the other knots follow
+    fn step(&mut self, direction: Dir) {
+        self.knots[0] = self.knots[0].walk(direction);
+        for i in 1..self.knots.len() {
+            let leader = self.knots[i - 1];
+            follow(&mut self.knots[i], leader);
+        }
+        for (knot, visited) in self.knots.iter().zip(self.visited.iter_mut()) {
+            visited.insert(*knot);
+        }
+    }
+
+    fn visited_by_tail(&self) -> usize {
+        self.visited.last().map_or(0, HashSet::len)
+    }
+
+    /// Render the state after each of a series of moves, like the puzzle does
+    fn frames(&mut self, moves: impl IntoIterator<Item = Move>) -> Vec<String> {
+        moves
+            .into_iter()
+            .map(|m| {
+                self.perform([m]);
+                self.to_string()
+            })
+            .collect()
+    }
 }
 
-fn lead(point: &mut (i32, i32), direction: &Direction) {
-    use Direction::*;
-    let d_head = match *direction {
-        R => (1, 0),
-        L => (-1, 0),
-        U => (0, 1),
-        D => (0, -1),
-    };
-    walk(point, d_head);
+/// Draws the head as `H`, other knots by their index (or `T` if there's just
+/// the one), and the start as `s`. Where knots overlap, the one nearest the
+/// head wins.
+impl Display for Rope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut grid = InfiniteGrid::new();
+        grid.insert(Point::ORIGIN, 's');
+        for (i, &knot) in self.knots.iter().enumerate().rev() {
+            let c = match i {
+                0 => 'H',
+                1 if self.knots.len() == 2 => 'T',
+                i => char::from_digit(i as u32 % 36, 36).unwrap_or('?'),
+            };
+            grid.insert(knot, c);
+        }
+        write!(f, "{grid}")
+    }
 }
 
-fn part2(_input: String) -> usize {
-    todo!()
+/// A knot that is no longer touching its leader moves one step towards it,
+/// diagonally if need be.
+fn follow(follower: &mut Point, leader: Point) {
+    if follower.chebyshev(leader) > 1 {
+        *follower += (leader - *follower).signum();
+    }
 }
 
 fn parse(input: &str) -> impl Iterator<Item = Move> + '_ {
     input.lines().flat_map(|line| line.parse::<Move>())
 }
+
+#[test]
+fn render_frames() {
+    let input = parse_test_file::<String>(220901);
+    let frames = Rope::new(10).frames(parse(&input));
+    assert_eq!(frames[0], "4321H\n");
+    assert_eq!(frames[1], "....H\n....1\n..432\n.5...\n6....\n");
+}
@@ -1,37 +1,88 @@
+use std::collections::BTreeMap;
+
 use crate::util::*;
 
-const RESULT1: &str = "\
-##..##..##..##..##..##..##..##..##..##..
-###...###...###...###...###...###...###.
-####....####....####....####....####....
-#####.....#####.....#####.....#####.....
-######......######......######......####
-#######.......#######.......#######.....";
-
-const RESULT2: &str = "\
-####.#....###..#....####..##..####.#....
-#....#....#..#.#.......#.#..#....#.#....
-###..#....#..#.#......#..#......#..#....
-#....#....###..#.....#...#.##..#...#....
-#....#....#....#....#....#..#.#....#....
-####.####.#....####.####..###.####.####.";
-
-aoc_test!(part1, 221001, 13140);
-aoc_test!(part1, 221000, 14780);
-aoc_test!(part2, 221001, RESULT1.to_string());
-aoc_test!(part2, 221000, RESULT2.to_string());
+aoc_register!(Day2210);
+aoc_test!(Day2210);
 
 #[derive(Debug, From)]
 struct Program {
-    ops: Vec<Op>,
+    ops: Vec<Box<dyn Instruction>>,
+}
+
+/// Registers are named by a single letter. They all start out at 0, apart
+/// from `x`, which starts out at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Registers(BTreeMap<char, i32>);
+
+impl Default for Registers {
+    fn default() -> Self {
+        Registers([('x', 1)].into_iter().collect())
+    }
+}
+
+impl Registers {
+    fn get(&self, reg: char) -> i32 {
+        self.0.get(&reg).copied().unwrap_or(0)
+    }
+
+    fn set(&mut self, reg: char, value: i32) {
+        self.0.insert(reg, value);
+    }
+}
+
+impl Display for Registers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let regs = self.0.iter().map(|(reg, value)| format!("{reg}={value}"));
+        write!(f, "{}", regs.format(" "))
+    }
+}
+
+/// Anything the CPU can execute. An instruction takes some number of cycles,
+/// and only takes effect once all of them are over.
+trait Instruction: Debug + Display {
+    fn cycles(&self) -> usize;
+
+    fn execute(&self, regs: &mut Registers);
+}
+
+#[derive(Debug)]
+struct Noop;
+
+impl Instruction for Noop {
+    fn cycles(&self) -> usize {
+        1
+    }
+
+    fn execute(&self, _regs: &mut Registers) {}
+}
+
+impl Display for Noop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "noop")
+    }
+}
+
+/// Adds a number to a register, like `addx 3`
+#[derive(Debug)]
+struct Add {
+    reg: char,
+    value: i32,
+}
+
+impl Instruction for Add {
+    fn cycles(&self) -> usize {
+        2
+    }
+
+    fn execute(&self, regs: &mut Registers) {
+        regs.set(self.reg, regs.get(self.reg) + self.value);
+    }
 }
 
-impl Program {
-    fn into_micro_instructions(self) -> impl Iterator<Item = i32> {
-        self.ops.into_iter().flat_map(|op| match op {
-            Op::Noop => vec![0],
-            Op::Addx(i) => vec![0, i],
-        })
+impl Display for Add {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "add{} {}", self.reg, self.value)
     }
 }
 
@@ -40,53 +91,208 @@ impl std::str::FromStr for Program {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use crate::util::aoc_nom::*;
-        let noop = tag("noop").map(|_| Op::Noop).into_str_parser();
-        let addx = preceded(tuple((tag("addx"), space1)), i32).map(Op::Addx);
-        let op = alt((noop, addx));
+        let noop = tag("noop")
+            .map(|_| Box::new(Noop) as Box<dyn Instruction>)
+            .into_str_parser();
+        let add = preceded(
+            tag("add"),
+            separated_pair(satisfy(|c| c.is_ascii_lowercase()), space1, i32),
+        )
+        .map(|(reg, value)| Box::new(Add { reg, value }) as Box<dyn Instruction>);
+        let op = alt((noop, add));
         let program = into(separated_list0(multispace1, op));
         program.anyhow(s)
     }
 }
 
-#[derive(Debug)]
-enum Op {
-    Noop,
-    Addx(i32),
-}
-
-fn part1(program: Program) -> i32 {
-    run(program)
-        .zip(1..)
-        .skip(19)
-        .step_by(40)
-        .map(|(cycle, strength)| cycle * strength)
-        .sum()
-}
-
-fn run(program: Program) -> impl Iterator<Item = i32> {
-    let mut x = 1;
-    program.into_micro_instructions().map(move |i| {
-        x += i;
-        x - i
-    })
-}
-
-fn draw_scan_line(line: impl Iterator<Item = i32>) -> String {
-    line.zip(0..)
-        .map(|(sprite_pos, i)| {
-            if (sprite_pos - 1..=sprite_pos + 1).contains(&i) {
-                '#'
-            } else {
-                '.'
+/// What the CPU is up to during some cycle
+struct Tick<'a> {
+    /// Cycles are counted from 1
+    cycle: usize,
+    pc: usize,
+    regs: &'a Registers,
+    instruction: &'a dyn Instruction,
+}
+
+/// Something that watches the CPU at work, cycle by cycle
+trait Observer {
+    fn during(&mut self, tick: &Tick);
+}
+
+impl<F: FnMut(&Tick)> Observer for F {
+    fn during(&mut self, tick: &Tick) {
+        self(tick)
+    }
+}
+
+struct Cpu<'a> {
+    program: &'a Program,
+    pc: usize,
+    cycle: usize,
+    regs: Registers,
+}
+
+impl<'a> Cpu<'a> {
+    fn new(program: &'a Program) -> Self {
+        Cpu {
+            program,
+            pc: 0,
+            cycle: 0,
+            regs: Registers::default(),
+        }
+    }
+
+    /// Run the program to the end, showing every cycle to every observer
+    fn run(&mut self, observers: &mut [&mut dyn Observer]) {
+        while let Some(instruction) = self.program.ops.get(self.pc) {
+            for _ in 0..instruction.cycles() {
+                self.cycle += 1;
+                let tick = Tick {
+                    cycle: self.cycle,
+                    pc: self.pc,
+                    regs: &self.regs,
+                    instruction: instruction.as_ref(),
+                };
+                for observer in observers.iter_mut() {
+                    observer.during(&tick);
+                }
             }
-        })
-        .join("")
+            instruction.execute(&mut self.regs);
+            self.pc += 1;
+        }
+    }
+}
+
+/// Adds up the signal strength during the 20th cycle, and every 40 cycles
+/// after that
+#[derive(Default)]
+struct SignalStrength(i32);
+
+impl Observer for SignalStrength {
+    fn during(&mut self, tick: &Tick) {
+        if tick.cycle % 40 == 20 {
+            self.0 += tick.cycle as i32 * tick.regs.get('x');
+        }
+    }
+}
+
+/// The screen, which draws a pixel every cycle, 40 pixels to a row. A pixel
+/// is lit if the sprite, which is 3 pixels wide and centered on `x`, covers
+/// it.
+#[derive(Default)]
+struct Crt(String);
+
+impl Observer for Crt {
+    fn during(&mut self, tick: &Tick) {
+        let col = (tick.cycle - 1) % 40;
+        if col == 0 && tick.cycle > 1 {
+            self.0.push('\n');
+        }
+        let lit = (tick.regs.get('x') - col as i32).abs() <= 1;
+        self.0.push(if lit { '#' } else { '.' });
+    }
+}
+
+/// Remembers everything the CPU did
+#[derive(Default)]
+struct Trace(Vec<String>);
+
+impl Observer for Trace {
+    fn during(&mut self, tick: &Tick) {
+        let Tick {
+            cycle,
+            pc,
+            regs,
+            instruction,
+        } = tick;
+        self.0
+            .push(format!("{cycle:>4} {pc:>4}  {regs}  {instruction}"));
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join("\n"))
+    }
+}
+
+struct Day2210;
+
+impl Solution for Day2210 {
+    type Input = Program;
+    type Output1 = i32;
+    type Output2 = String;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        10
+    }
+
+    fn part1(program: Program) -> i32 {
+        let mut strength = SignalStrength::default();
+        Cpu::new(&program).run(&mut [&mut strength]);
+        strength.0
+    }
+
+    /// The screen spells out eight letters, which we read back. The example
+    /// program just draws a pattern that isn't letters, and there may be
+    /// letters the font doesn't know, so if we can't read the screen we
+    /// return the picture.
+    fn part2(program: Program) -> String {
+        let mut crt = Crt::default();
+        Cpu::new(&program).run(&mut [&mut crt]);
+        aoc::ocr::SMALL.read(&crt.0).unwrap_or(crt.0)
+    }
 }
 
-fn part2(program: Program) -> String {
-    run(program)
-        .chunks(40)
-        .into_iter()
-        .map(draw_scan_line)
-        .join("\n")
+#[test]
+fn test_trace() {
+    let program: Program = "noop\naddx 3\naddx -5".parse().unwrap();
+    let mut trace = Trace::default();
+    let mut xs = vec![];
+    let mut during = |tick: &Tick| xs.push(tick.regs.get('x'));
+    let mut cpu = Cpu::new(&program);
+    cpu.run(&mut [&mut trace, &mut during]);
+    assert_eq!(xs, [1, 1, 1, 4, 4]);
+    assert_eq!(cpu.regs.get('x'), -1);
+    let expected = "   1    0  x=1  noop
+   2    1  x=1  addx 3
+   3    1  x=1  addx 3
+   4    2  x=4  addx -5
+   5    2  x=4  addx -5";
+    assert_eq!(trace.to_string(), expected);
+}
+
+#[test]
+fn test_custom_instruction() {
+    /// Multiplies two registers into the first, and takes its time doing so
+    #[derive(Debug)]
+    struct Mul(char, char);
+
+    impl Instruction for Mul {
+        fn cycles(&self) -> usize {
+            3
+        }
+
+        fn execute(&self, regs: &mut Registers) {
+            regs.set(self.0, regs.get(self.0) * regs.get(self.1));
+        }
+    }
+
+    impl Display for Mul {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "mul {} {}", self.0, self.1)
+        }
+    }
+
+    let mut program: Program = "addy 6\naddx 1".parse().unwrap();
+    program.ops.push(Box::new(Mul('x', 'y')));
+    let mut cycles = 0;
+    let mut cpu = Cpu::new(&program);
+    cpu.run(&mut [&mut |_: &Tick| cycles += 1]);
+    assert_eq!(cycles, 7);
+    assert_eq!(cpu.regs.to_string(), "x=12 y=6");
 }
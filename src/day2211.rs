@@ -1,9 +1,7 @@
 use crate::util::*;
 
-aoc_test!(part1, 221101, 10605);
-aoc_test!(part1, 221100, 111210);
-aoc_test!(part2, 221101, 2713310158);
-aoc_test!(part2, 221100, 15447387620);
+aoc_register!(Day2211);
+aoc_test!(Day2211);
 
 #[derive(Debug, From)]
 struct Monkeys {
@@ -19,28 +17,50 @@ impl Monkeys {
 #[derive(Debug, From)]
 struct Monkey {
     items: Vec<u64>,
-    operation: Operation,
+    operation: Expr,
     decision: Decision,
 }
 
-#[derive(Debug)]
-enum Operation {
-    Add(u64),
-    Mul(u64),
-    Sqr,
+/// A monkey's operation: an arithmetic expression over the `old` worry
+/// level and constants
+#[derive(Debug, Clone)]
+enum Expr {
+    Old,
+    Const(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
 }
 
-impl Operation {
-    fn apply(&self, x: u64) -> u64 {
-        match self {
-            Operation::Add(i) => x + i,
-            Operation::Mul(i) => x * i,
-            Operation::Sqr => x * x,
+impl Expr {
+    /// Evaluate the expression modulo `modulus`, which keeps the numbers
+    /// small, but still gives the right answer to any divisibility test by a
+    /// divisor of `modulus`. Without a modulus, we evaluate exactly, which
+    /// fails if the worry level goes negative or doesn't fit in a `u64`.
+    fn eval(&self, old: u64, modulus: Option<u64>) -> anyhow::Result<u64> {
+        // Both operands fit in a `u64`, so their sum or product fits in a
+        // `u128`.
+        let eval = |e: &Expr| e.eval(old, modulus).map(u128::from);
+        let x = match (self, modulus) {
+            (Expr::Old, _) => old as u128,
+            (Expr::Const(c), _) => *c as u128,
+            (Expr::Add(a, b), _) => eval(a)? + eval(b)?,
+            (Expr::Sub(a, b), Some(m)) => eval(a)? + m as u128 - eval(b)?,
+            (Expr::Sub(a, b), None) => {
+                let (a, b) = (eval(a)?, eval(b)?);
+                a.checked_sub(b)
+                    .ok_or_else(|| anyhow!("worry level {a} - {b} is negative"))?
+            }
+            (Expr::Mul(a, b), _) => eval(a)? * eval(b)?,
+        };
+        match modulus {
+            Some(m) => Ok((x % m as u128) as u64),
+            None => u64::try_from(x).map_err(|_| anyhow!("worry level {x} is too large")),
         }
     }
 }
 
-#[derive(derive_more::From, Debug)]
+#[derive(Debug)]
 struct Decision {
     divisor: u64,
     if_yes: u64,
@@ -49,7 +69,7 @@ struct Decision {
 
 impl Decision {
     fn apply(&self, x: u64) -> u64 {
-        if x % self.divisor == 0 {
+        if x.is_multiple_of(self.divisor) {
             self.if_yes
         } else {
             self.if_no
@@ -64,53 +84,293 @@ impl FromStr for Monkeys {
         use crate::util::aoc_nom::*;
         let label = || tuple((many0(none_of(":")), tag(":"), space0));
         let items = preceded(label(), separated_list1(tag(", "), u64));
-        let sqr = tag("* old").map(|_| Operation::Sqr);
-        let mul = preceded(tag("* "), u64).map(Operation::Mul);
-        let add = preceded(tag("+ "), u64).map(Operation::Add);
-        let operation = preceded(many0(none_of("*+")), alt((sqr, mul, add)));
-        let bla = || preceded(many0(none_of("0123456789")), u64);
-        let decision = tuple((bla(), bla(), bla())).map(Decision::from);
+        let operation = preceded(tuple((label(), tag("new"), space0, tag("="))), expr);
+        // Numbers in the test come with their line, so we can complain about
+        // them once we know how many monkeys there are
+        let line = || {
+            peek(rest).map(|rest: &str| {
+                let offset = rest.as_ptr() as usize - s.as_ptr() as usize;
+                s[..offset].matches('\n').count() + 1
+            })
+        };
+        let bla = || preceded(many0(none_of("0123456789")), pair(line(), u64));
+        let decision = tuple((bla(), bla(), bla()));
+        let items = context("starting items", items);
+        let operation = context("operation", operation);
+        let decision = context("test", decision);
         let monkey = preceded(label(), tuple((items, operation, decision)));
-        let monkey = monkey.map(Monkey::from);
-        let monkeys = terminated(many1(monkey), tuple((multispace0, eof)));
-        let monkeys = monkeys.map(Monkeys::from);
-        monkeys.anyhow(s)
+        let monkey = context("monkey", monkey);
+        let monkeys = many1(monkey).anyhow(s)?;
+        let count = monkeys.len();
+        let monkeys = monkeys.into_iter().map(|(items, operation, decision)| {
+            let ((line, divisor), (yes_line, if_yes), (no_line, if_no)) = decision;
+            if divisor == 0 {
+                return Err(anyhow!("line {line}: can't test for divisibility by 0"));
+            }
+            for (line, target) in [(yes_line, if_yes), (no_line, if_no)] {
+                if target >= count as u64 {
+                    return Err(anyhow!("line {line}: there is no monkey {target}"));
+                }
+            }
+            let decision = Decision {
+                divisor,
+                if_yes,
+                if_no,
+            };
+            Ok(Monkey::from((items, operation, decision)))
+        });
+        Ok(Monkeys::new(monkeys.try_collect()?))
     }
 }
 
-fn part1(Monkeys { mut monkeys }: Monkeys) -> u64 {
-    let mut activity = monkeys.iter().map(|_| 0_u64).collect_vec();
-    for _ in 0..20 {
-        for i in 0..monkeys.len() {
-            let items = std::mem::take(&mut monkeys[i].items);
-            for item in items {
-                activity[i] += 1;
-                let item = monkeys[i].operation.apply(item) / 3;
-                let target = monkeys[i].decision.apply(item) as usize;
-                monkeys[target].items.push(item);
+/// Expressions are sums and differences of products of atoms
+fn expr(s: &str) -> aoc_nom::IResult<&str, Expr> {
+    use crate::util::aoc_nom::*;
+    let (s, first) = term(s)?;
+    let op = delimited(space0, one_of("+-"), space0);
+    let mut terms = fold_many0(
+        pair(op, term),
+        move || first.clone(),
+        |x, (op, y)| match op {
+            '+' => Expr::Add(Box::new(x), Box::new(y)),
+            _ => Expr::Sub(Box::new(x), Box::new(y)),
+        },
+    );
+    terms(s)
+}
+
+fn term(s: &str) -> aoc_nom::IResult<&str, Expr> {
+    use crate::util::aoc_nom::*;
+    let (s, first) = atom(s)?;
+    let op = delimited(space0, char('*'), space0);
+    let mut factors = fold_many0(
+        preceded(op, atom),
+        move || first.clone(),
+        |x, y| Expr::Mul(Box::new(x), Box::new(y)),
+    );
+    factors(s)
+}
+
+fn atom(s: &str) -> aoc_nom::IResult<&str, Expr> {
+    use crate::util::aoc_nom::*;
+    let old = tag("old").map(|_| Expr::Old);
+    let constant = u64.map(Expr::Const);
+    let parens = delimited(pair(char('('), space0), expr, pair(space0, char(')')));
+    preceded(space0, alt((old, constant, parens)))(s)
+}
+
+/// The rules of the game: how much relief we feel after each inspection, and
+/// how many rounds we watch the monkeys for
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    relief: u64,
+    rounds: u64,
+}
+
+impl Rules {
+    /// Relief divides worry levels, so it can't be 0
+    fn new(relief: u64, rounds: u64) -> anyhow::Result<Self> {
+        if relief == 0 {
+            return Err(anyhow!("relief has to be at least 1"));
+        }
+        Ok(Rules { relief, rounds })
+    }
+}
+
+/// An item, as the monkey holding it at the start of a round along with its
+/// worry level
+type Item = (usize, u64);
+
+impl Monkeys {
+    /// The product of the activity of the two most active monkeys
+    fn business(&self, rules: Rules) -> anyhow::Result<u128> {
+        use rayon::prelude::*;
+        let modulus = self.modulus(rules.relief)?;
+        // Items never affect each other, so we can follow each one on its own
+        // and add up how often each monkey inspects it.
+        let items = self
+            .monkeys
+            .iter()
+            .enumerate()
+            .flat_map(|(i, monkey)| monkey.items.iter().map(move |&worry| (i, worry)))
+            .collect_vec();
+        let activity = items
+            .par_iter()
+            .map(|&item| self.activity(item, rules, modulus))
+            .reduce(
+                || vec![0; self.monkeys.len()],
+                |a, b| a.into_iter().zip(b).map(|(a, b)| a + b).collect(),
+            );
+        Ok(activity
+            .into_iter()
+            .sorted()
+            .rev()
+            .take(2)
+            .map(u128::from)
+            .product())
+    }
+
+    /// Working modulo a multiple of all divisors doesn't change any of the
+    /// tests. That doesn't survive division though, so with relief we work
+    /// with the actual worry levels.
+    fn modulus(&self, relief: u64) -> anyhow::Result<Option<u64>> {
+        if relief != 1 {
+            return Ok(None);
+        }
+        let lcm = self
+            .monkeys
+            .iter()
+            .try_fold(1, |m, monkey| {
+                aoc::num::lcm(m, monkey.decision.divisor.into())
+            })
+            .and_then(|m| u64::try_from(m).ok());
+        let lcm = lcm.ok_or_else(|| anyhow!("the divisors have no common multiple in a u64"))?;
+        Ok(Some(lcm))
+    }
+
+    /// How often each monkey inspects a single item, working modulo
+    /// `modulus` if there is one
+    fn activity(
+        &self,
+        item: Item,
+        Rules { relief, rounds }: Rules,
+        modulus: Option<u64>,
+    ) -> Vec<u64> {
+        // Once its worry level is reduced, an item can only be in so many
+        // states, so it ends up going round in circles. Remember who
+        // inspected it in each round until then, and count how often each
+        // of those rounds comes up. With relief, worry levels are exact, and
+        // may never repeat, but then we only need to look at `rounds` of
+        // them.
+        let mut inspections = vec![];
+        let cycle = aoc::cycle::find_within(
+            item,
+            |&item| {
+                let mut inspected = vec![];
+                let next = self.round(item, relief, modulus, |i| inspected.push(i));
+                inspections.push(inspected);
+                next
+            },
+            rounds,
+        );
+        let mut activity = vec![0; self.monkeys.len()];
+        for (k, inspected) in inspections.iter().enumerate() {
+            let visits = cycle.as_ref().map_or(1, |cycle| cycle.visits(k, rounds));
+            for &i in inspected {
+                activity[i] += visits;
             }
         }
+        activity
     }
-    activity.into_iter().sorted().rev().take(2).product::<u64>()
-}
-
-fn part2(Monkeys { mut monkeys }: Monkeys) -> u64 {
-    let mut activity = monkeys.iter().map(|_| 0_u64).collect_vec();
-    let field: u64 = monkeys
-        .iter()
-        .map(|monkey| monkey.decision.divisor)
-        .product();
-    for _ in 0..10000 {
-        for i in 0..monkeys.len() {
-            let items = std::mem::take(&mut monkeys[i].items);
-            for item in items {
-                activity[i] += 1;
-                let item = monkeys[i].operation.apply(item);
-                let item = item % field;
-                let target = monkeys[i].decision.apply(item) as usize;
-                monkeys[target].items.push(item);
+
+    /// Follow an item through one round. Monkeys take turns in order, so an
+    /// item thrown to a monkey further down the line gets inspected again in
+    /// the same round. Panics if an exact worry level goes out of range.
+    fn round(
+        &self,
+        (mut i, mut worry): Item,
+        relief: u64,
+        modulus: Option<u64>,
+        mut inspect: impl FnMut(usize),
+    ) -> Item {
+        loop {
+            let monkey = &self.monkeys[i];
+            inspect(i);
+            worry = match monkey.operation.eval(worry, modulus) {
+                Ok(worry) => worry / relief,
+                Err(e) => panic!("monkey {i}: {e}"),
+            };
+            let target = monkey.decision.apply(worry) as usize;
+            if target <= i {
+                return (target, worry);
+            }
+            i = target;
+        }
+    }
+}
+
+struct Day2211;
+
+impl Solution for Day2211 {
+    type Input = Monkeys;
+    type Output1 = u128;
+    type Output2 = u128;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        11
+    }
+
+    fn part1(monkeys: Monkeys) -> u128 {
+        let rules = Rules::new(3, 20).unwrap();
+        monkeys.business(rules).expect("monkey business")
+    }
+
+    fn part2(monkeys: Monkeys) -> u128 {
+        let rules = Rules::new(1, 10000).unwrap();
+        monkeys.business(rules).expect("monkey business")
+    }
+}
+
+#[test]
+fn test_expr() {
+    use crate::util::aoc_nom::*;
+    let e = expr
+        .into_str_parser()
+        .anyhow("old * (old - 3) + 2 * old")
+        .unwrap();
+    assert_eq!(e.eval(10, None).unwrap(), 90);
+    assert_eq!(e.eval(10, Some(7)).unwrap(), 90 % 7);
+    assert_eq!(e.eval(2, Some(7)).unwrap(), 2);
+    assert!(e.eval(2, None).is_err());
+    let e = expr.into_str_parser().anyhow("old * old * old").unwrap();
+    assert!(e.eval(1 << 30, None).is_err());
+    assert!(expr.into_str_parser().anyhow("old / 2").is_err());
+}
+
+#[test]
+fn test_fast_forward() {
+    let monkeys: Monkeys = parse_test_file(221101);
+    for (relief, rounds) in [(1, 1000), (3, 20)] {
+        check_fast_forward(&monkeys, relief, rounds);
+    }
+    let rules = Rules::new(1, 1_000_000_000_000).unwrap();
+    assert!(monkeys.business(rules).unwrap() > 2713310158);
+    assert!(Rules::new(0, 20).is_err());
+}
+
+#[test]
+fn test_errors() {
+    let text = load_input::<String>(221101).unwrap();
+    let error = |from: &str, to: &str| {
+        let text = text.replacen(from, to, 1);
+        text.parse::<Monkeys>().unwrap_err().to_string()
+    };
+    assert_eq!(
+        error("divisible by 23", "divisible by 0"),
+        "line 4: can't test for divisibility by 0"
+    );
+    assert_eq!(
+        error("If false: throw to monkey 3", "If false: throw to monkey 4"),
+        "line 6: there is no monkey 4"
+    );
+}
+
+#[cfg(test)]
+fn check_fast_forward(monkeys: &Monkeys, relief: u64, rounds: u64) {
+    let modulus = monkeys.modulus(relief).unwrap();
+    for monkey in 0..monkeys.monkeys.len() {
+        for &worry in &monkeys.monkeys[monkey].items {
+            let mut activity = vec![0; monkeys.monkeys.len()];
+            let mut item = (monkey, worry);
+            for _ in 0..rounds {
+                item = monkeys.round(item, relief, modulus, |i| activity[i] += 1);
             }
+            let rules = Rules::new(relief, rounds).unwrap();
+            assert_eq!(monkeys.activity((monkey, worry), rules, modulus), activity);
         }
     }
-    activity.into_iter().sorted().rev().take(2).product::<u64>()
 }
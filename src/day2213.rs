@@ -5,22 +5,45 @@ use nom::multi::separated_list1;
 
 use crate::util::*;
 
-aoc_test!(part1, 221301, 13);
-aoc_test!(part1, 221300, 6415);
+aoc_register!(Day2213);
+aoc_test!(Day2213);
 
 struct Signal(Vec<PacketPair>);
 
 #[derive(From, Debug)]
 struct PacketPair(Packet, Packet);
 
-#[derive(Debug)]
+/// Packets are ordered as the puzzle describes: numbers by value, lists
+/// lexicographically, and a number compared to a list as if it were a list
+/// holding just that number. That makes `2` and `[[2]]` equal.
+#[derive(Debug, Clone)]
 enum Packet {
     Number(u32),
     List(Vec<Packet>),
 }
 
-fn part1(signal: Signal) -> usize {
-    signal.score()
+struct Day2213;
+
+impl Solution for Day2213 {
+    type Input = Signal;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2022
+    }
+
+    fn day() -> u8 {
+        13
+    }
+
+    fn part1(signal: Signal) -> usize {
+        signal.score()
+    }
+
+    fn part2(signal: Signal) -> usize {
+        signal.decoder_key()
+    }
 }
 
 impl Signal {
@@ -31,61 +54,144 @@ impl Signal {
             .flat_map(|(pp, i)| pp.is_sorted().then_some(i))
             .sum()
     }
+
+    /// Sort all packets along with the two divider packets, and multiply the
+    /// (1-based) indices at which the dividers end up. We don't need to sort
+    /// to find those: a divider goes after all packets that are less than
+    /// it. Packets that are equal to a divider, like `[2]`, go after it.
+    fn decoder_key(&self) -> usize {
+        let [two, six]: [Packet; 2] = ["[[2]]".parse().unwrap(), "[[6]]".parse().unwrap()];
+        let packets = self.0.iter().flat_map(|PacketPair(p, q)| [p, q]);
+        let before = |divider: &Packet| packets.clone().filter(|p| *p < divider).count();
+        // The `[[6]]` divider also goes after the `[[2]]` one
+        (1 + before(&two)) * (2 + before(&six))
+    }
 }
 
 impl PacketPair {
     fn is_sorted(&self) -> bool {
-        self.0.order(&self.1) != Ordering::Greater
+        self.0 <= self.1
     }
 }
 
-impl Packet {
-    fn order(&self, other: &Self) -> Ordering {
-        fn cmp_n(pp: &[Packet], qq: &[Packet]) -> Ordering {
-            use itertools::EitherOrBoth::*;
-            use Ordering::*;
-            for pq in pp.iter().zip_longest(qq.iter()) {
-                match pq {
-                    Both(p, q) => match cmp_1(p, q) {
-                        Less => return Less,
-                        Equal => {}
-                        Greater => return Greater,
-                    },
-                    Left(_) => return Greater,
-                    Right(_) => return Less,
-                }
-            }
-            Equal
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use Packet::*;
+        match (self, other) {
+            (Number(p), Number(q)) => p.cmp(q),
+            (Number(_), List(qq)) => std::slice::from_ref(self).cmp(qq.as_slice()),
+            (List(pp), Number(_)) => pp.as_slice().cmp(std::slice::from_ref(other)),
+            (List(pp), List(qq)) => pp.cmp(qq),
         }
-        fn cmp_1(p: &Packet, q: &Packet) -> Ordering {
-            use Packet::*;
-            match (p, q) {
-                (Number(p), Number(q)) => p.cmp(q),
-                (Number(p), List(qq)) => cmp_n(&[Number(*p)], qq),
-                (List(pp), Number(q)) => cmp_n(pp, &[Number(*q)]),
-                (List(pp), List(qq)) => cmp_n(pp, qq),
-            }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Equality has to agree with the ordering, so this isn't structural
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+/// Prints a packet the way it's written in the input
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Number(n) => write!(f, "{n}"),
+            Packet::List(pp) => write!(f, "[{}]", pp.iter().join(",")),
         }
-        cmp_1(self, other)
     }
 }
 
-impl FromStr for Signal {
+/// Packets happen to be JSON, so they can be converted to JSON values
+impl From<&Packet> for serde_json::Value {
+    fn from(packet: &Packet) -> Self {
+        match packet {
+            Packet::Number(n) => (*n).into(),
+            Packet::List(pp) => pp.iter().map(serde_json::Value::from).collect(),
+        }
+    }
+}
+
+/// Not every JSON value is a packet though: only lists and numbers that fit
+/// in a `u32` are.
+impl TryFrom<&serde_json::Value> for Packet {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        use serde_json::Value;
+        match value {
+            Value::Number(n) => n
+                .as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .map(Packet::Number)
+                .ok_or_else(|| anyhow!("{n} is not a valid packet number")),
+            Value::Array(vv) => vv
+                .iter()
+                .map(Packet::try_from)
+                .try_collect()
+                .map(Packet::List),
+            _ => Err(anyhow!("{value} is not a valid packet")),
+        }
+    }
+}
+
+fn packet(s: &str) -> aoc_nom::IResult<&str, Packet> {
+    use aoc_nom::*;
+    alt((
+        u32.map(Packet::Number),
+        delimited(tag("["), separated_list0(tag(","), packet), tag("]")).map(Packet::List),
+    ))
+    .parse(s)
+}
+
+impl FromStr for Packet {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use aoc_nom::*;
+        packet.into_str_parser().anyhow(s)
+    }
+}
 
-        fn packet(s: &str) -> IResult<&str, Packet> {
-            alt((
-                u32.map(Packet::Number),
-                delimited(tag("["), separated_list0(tag(","), packet), tag("]")).map(Packet::List),
-            ))
-            .parse(s)
-        }
+impl FromStr for Signal {
+    type Err = anyhow::Error;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use aoc_nom::*;
         let packet_pair = separated_pair(packet, multispace1, packet).map(PacketPair::from);
         let signal = separated_list1(multispace1, packet_pair).map(Signal);
         signal.anyhow(s)
     }
 }
+
+#[test]
+fn test_packets() {
+    let text = "[1,[2,[3,[4,[5,6,7]]]],8,9]";
+    let packet: Packet = text.parse().unwrap();
+    assert_eq!(packet.to_string(), text);
+    let json: serde_json::Value = serde_json::from_str(text).unwrap();
+    assert_eq!(serde_json::Value::from(&packet), json);
+    assert_eq!(Packet::try_from(&json).unwrap().to_string(), text);
+    assert!(Packet::try_from(&serde_json::json!([1, -2])).is_err());
+    assert!(Packet::try_from(&serde_json::json!({"a": 1})).is_err());
+    let p = |s: &str| s.parse::<Packet>().unwrap();
+    assert_eq!(p("2"), p("[[2]]"));
+    assert!(p("[[1],[2,3,4]]") < p("[[1],4]"));
+    assert!(p("[[[]]]") > p("[[]]"));
+    assert!("[1,2".parse::<Packet>().is_err());
+}
+
+#[test]
+fn test_decoder_key() {
+    let signal: Signal = "[2]\n[[[6]]]\n\n[1]\n[3]".parse().unwrap();
+    assert_eq!(signal.decoder_key(), 2 * 5);
+}
@@ -1,16 +1,30 @@
 use crate::util::*;
 
-aoc_test!(part1, 230101, 142);
-aoc_test!(part1, 230100, 56108);
-aoc_test!(part2, 230102, 281);
-aoc_test!(part2, 230100, 55652);
+aoc_register!(Day2301);
+aoc_test!(Day2301);
 
-fn part1(input: String) -> u32 {
-    input.lines().map(digits1).map(score).sum()
-}
+struct Day2301;
+
+impl Solution for Day2301 {
+    type Input = String;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        1
+    }
+
+    fn part1(input: String) -> u32 {
+        input.lines().map(digits1).map(score).sum()
+    }
 
-fn part2(input: String) -> u32 {
-    input.lines().map(digits2).map(score).sum()
+    fn part2(input: String) -> u32 {
+        input.lines().map(digits2).map(score).sum()
+    }
 }
 
 fn score(digits: Vec<u32>) -> u32 {
@@ -1,43 +1,57 @@
 use crate::util::*;
 
-aoc_test!(part1, 230201, 8);
-aoc_test!(part1, 230200, 2776);
-aoc_test!(part2, 230201, 2286);
-aoc_test!(part2, 230200, 68638);
+aoc_register!(Day2302);
+aoc_test!(Day2302);
 
-fn part1(input: String) -> u32 {
-    let limit_counter = Counter::new(12, 13, 14);
-    input
-        .lines()
-        .map(parse)
-        .zip(1..)
-        .map(|(counters, id)| {
-            if counters
-                .fold(Counter::default(), |max_counter, counter| {
-                    max_counter.update_max(&counter)
-                })
-                .fits_inside(&limit_counter)
-            {
-                id
-            } else {
-                0
-            }
-        })
-        .sum()
-}
+struct Day2302;
+
+impl Solution for Day2302 {
+    type Input = String;
+    type Output1 = u32;
+    type Output2 = u32;
 
-fn part2(input: String) -> u32 {
-    input
-        .lines()
-        .map(parse)
-        .map(|counters| {
-            counters
-                .fold(Counter::default(), |max_counter, counter| {
-                    max_counter.update_max(&counter)
-                })
-                .power()
-        })
-        .sum()
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        2
+    }
+
+    fn part1(input: String) -> u32 {
+        let limit_counter = Counter::new(12, 13, 14);
+        input
+            .lines()
+            .map(parse)
+            .zip(1..)
+            .map(|(counters, id)| {
+                if counters
+                    .fold(Counter::default(), |max_counter, counter| {
+                        max_counter.update_max(&counter)
+                    })
+                    .fits_inside(&limit_counter)
+                {
+                    id
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    fn part2(input: String) -> u32 {
+        input
+            .lines()
+            .map(parse)
+            .map(|counters| {
+                counters
+                    .fold(Counter::default(), |max_counter, counter| {
+                        max_counter.update_max(&counter)
+                    })
+                    .power()
+            })
+            .sum()
+    }
 }
 
 #[derive(Default, Debug)]
@@ -1,12 +1,12 @@
 use crate::util::*;
 
-aoc_test!(part1, 230301, 4361);
-aoc_test!(part1, 230300, 525181);
-aoc_test!(part2, 230301, 467835);
-aoc_test!(part2, 230300, 84289137);
+use aoc_grid::*;
+
+aoc_register!(Day2303);
+aoc_test!(Day2303);
 
 struct Input {
-    grid: ndarray::Array2<char>,
+    grid: Grid<char>,
     numbers: Vec<Number>,
 }
 
@@ -21,7 +21,7 @@ impl FromStr for Input {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let grid = parse_grid(s)?;
+        let grid = s.parse()?;
         let numbers = parse_numbers(s)?;
         Ok(Input { grid, numbers })
     }
@@ -43,59 +43,51 @@ fn parse_numbers(s: &str) -> Result<Vec<Number>, anyhow::Error> {
     Ok(out)
 }
 
-fn parse_grid(s: &str) -> Result<ndarray::Array2<char>, anyhow::Error> {
-    // Surely there's a more elegant way to do this...
-    let mut xx = 0;
-    let mut yy = 0;
-    let chars = s
-        .lines()
-        .enumerate()
-        .flat_map(|(x, line)| {
-            xx = std::cmp::max(xx, x + 1);
-            yy = std::cmp::max(yy, line.len());
-            line.chars()
-        })
-        .collect_vec();
-    let chars = ndarray::Array::from_vec(chars);
-    let chars = chars.into_shape((xx, yy))?;
-    Ok(chars)
-}
+struct Day2303;
 
-fn part1(Input { grid, numbers }: Input) -> u32 {
-    numbers
-        .into_iter()
-        .flat_map(|number| {
-            margin(&number, &grid)
-                .any(|(_, c)| !"0123456789.".contains(*c))
-                .then_some(number.n)
-        })
-        .sum()
-}
+impl Solution for Day2303 {
+    type Input = Input;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn year() -> u16 {
+        2023
+    }
 
-fn part2(Input { grid, numbers }: Input) -> u32 {
-    numbers
-        .into_iter()
-        .flat_map(|number| {
-            margin(&number, &grid)
-                .filter_map(|(i, c)| (c == &'*').then_some((i, number.n)))
-                .collect_vec()
-        })
-        .into_group_map()
-        .values()
-        .filter_map(|ns| ns.iter().collect_tuple().map(|(a, b)| a * b))
-        .sum()
+    fn day() -> u8 {
+        3
+    }
+
+    fn part1(Input { grid, numbers }: Input) -> u32 {
+        numbers
+            .into_iter()
+            .flat_map(|number| {
+                margin(&number, &grid)
+                    .any(|(_, c)| !"0123456789.".contains(*c))
+                    .then_some(number.n)
+            })
+            .sum()
+    }
+
+    fn part2(Input { grid, numbers }: Input) -> u32 {
+        numbers
+            .into_iter()
+            .flat_map(|number| {
+                margin(&number, &grid)
+                    .filter_map(|(pos, c)| (c == &'*').then_some((pos, number.n)))
+                    .collect_vec()
+            })
+            .into_group_map()
+            .values()
+            .filter_map(|ns| ns.iter().collect_tuple().map(|(a, b)| a * b))
+            .sum()
+    }
 }
 
-fn margin<'a, T>(
-    number: &'a Number,
-    array: &'a ndarray::Array2<T>,
-) -> impl Iterator<Item = ((usize, usize), &'a T)> {
-    let (xx, yy) = array.dim();
-    let xx_hi = xx.saturating_sub(1);
-    let yy_hi = yy.saturating_sub(1);
-    let x_lo = number.x.saturating_sub(1).clamp(0, xx_hi);
-    let x_hi = number.x.saturating_add(1).clamp(0, xx_hi);
-    let y_lo = number.y_start.saturating_sub(1).clamp(0, yy_hi);
-    let y_hi = number.y_end.saturating_add(1).clamp(0, yy_hi);
-    itertools::iproduct!(x_lo..=x_hi, y_lo..y_hi).flat_map(|i| array.get(i).map(|t| (i, t)))
+/// All tiles adjacent to a number, including diagonally
+fn margin<'a>(number: &'a Number, grid: &'a Grid<char>) -> impl Iterator<Item = (Pos, &'a char)> {
+    (number.y_start..number.y_end)
+        .flat_map(|y| grid.neighbours8(Pos(number.x, y)))
+        .unique()
+        .map(|pos| (pos, &grid[pos]))
 }
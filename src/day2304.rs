@@ -1,9 +1,7 @@
 use crate::util::*;
 
-aoc_test!(part1, 230401, 13);
-aoc_test!(part1, 230400, 19135);
-aoc_test!(part2, 230401, 30);
-aoc_test!(part2, 230400, 5704953);
+aoc_register!(Day2304);
+aoc_test!(Day2304);
 
 struct Cards(Vec<Card>);
 
@@ -55,26 +53,42 @@ impl Card {
     }
 }
 
-fn part1(cards: Cards) -> usize {
-    let scores = cards.matches().into_iter().map(|n| {
-        if n > 0 {
-            2_usize.pow((n - 1) as u32)
-        } else {
-            0
-        }
-    });
-    scores.sum()
-}
+struct Day2304;
+
+impl Solution for Day2304 {
+    type Input = Cards;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2023
+    }
 
-fn part2(cards: Cards) -> usize {
-    let scores: Vec<usize> = cards.matches();
-    fn add_up(scores: &[usize], i: usize) -> usize {
-        (i + 1..=i + scores[i])
-            .map(|j| add_up(scores, j))
-            .sum::<usize>()
-            + 1
+    fn day() -> u8 {
+        4
+    }
+
+    fn part1(cards: Cards) -> usize {
+        let scores = cards.matches().into_iter().map(|n| {
+            if n > 0 {
+                2_usize.pow((n - 1) as u32)
+            } else {
+                0
+            }
+        });
+        scores.sum()
+    }
+
+    fn part2(cards: Cards) -> usize {
+        let scores: Vec<usize> = cards.matches();
+        fn add_up(scores: &[usize], i: usize) -> usize {
+            (i + 1..=i + scores[i])
+                .map(|j| add_up(scores, j))
+                .sum::<usize>()
+                + 1
+        }
+        (0..scores.len())
+            .map(|i| add_up(scores.as_slice(), i))
+            .sum()
     }
-    (0..scores.len())
-        .map(|i| add_up(scores.as_slice(), i))
-        .sum()
 }
@@ -1,9 +1,7 @@
 use crate::util::*;
 
-aoc_test!(part1, 230601, 288);
-aoc_test!(part1, 230600, 1710720);
-aoc_test!(part2, 230601, 71503);
-aoc_test!(part2, 230600, 35349468);
+aoc_register!(Day2306);
+aoc_test!(Day2306);
 
 struct Races {
     races: Vec<Race>,
@@ -34,12 +32,28 @@ impl FromStr for Races {
     }
 }
 
-fn part1(races: Races) -> u64 {
-    races.ways_to_win().product()
-}
+struct Day2306;
+
+impl Solution for Day2306 {
+    type Input = Races;
+    type Output1 = u64;
+    type Output2 = u64;
 
-fn part2(races: Races) -> u64 {
-    races.combine().ways_to_win()
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        6
+    }
+
+    fn part1(races: Races) -> u64 {
+        races.ways_to_win().product()
+    }
+
+    fn part2(races: Races) -> u64 {
+        races.combine().ways_to_win()
+    }
 }
 
 impl Race {
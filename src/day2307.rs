@@ -1,16 +1,30 @@
 use crate::util::*;
 
-aoc_test!(part1, 230701, 6440);
-aoc_test!(part1, 230700, 250347426);
-aoc_test!(part2, 230701, 5905);
-aoc_test!(part2, 230700, 251224870);
+aoc_register!(Day2307);
+aoc_test!(Day2307);
 
-fn part1(mut hands: Hands) -> i64 {
-    hands.winnings(Hand::key1)
-}
+struct Day2307;
+
+impl Solution for Day2307 {
+    type Input = Hands;
+    type Output1 = i64;
+    type Output2 = i64;
 
-fn part2(mut hands: Hands) -> i64 {
-    hands.winnings(Hand::key2)
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        7
+    }
+
+    fn part1(mut hands: Hands) -> i64 {
+        hands.winnings(Hand::key1)
+    }
+
+    fn part2(mut hands: Hands) -> i64 {
+        hands.winnings(Hand::key2)
+    }
 }
 
 #[derive(Debug)]
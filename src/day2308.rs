@@ -1,9 +1,7 @@
 use crate::util::*;
 
-aoc_test!(part1, 230801, 2);
-aoc_test!(part1, 230800, 16897);
-aoc_test!(part2, 230802, 6);
-aoc_test!(part2, 230800, 16563603485021);
+aoc_register!(Day2308);
+aoc_test!(Day2308);
 
 #[derive(From)]
 struct Instructions {
@@ -42,31 +40,98 @@ impl FromStr for Instructions {
     }
 }
 
-fn part1(instructions: Instructions) -> i64 {
-    let (n, _) = path(&instructions, "AAA")
-        .find_position(|&n| n == "ZZZ")
-        .expect("camel got lost");
-    n as i64
+struct Day2308;
+
+impl Solution for Day2308 {
+    type Input = Instructions;
+    type Output1 = i64;
+    type Output2 = i64;
+
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        8
+    }
+
+    fn part1(instructions: Instructions) -> i64 {
+        let (n, _) = path(&instructions, "AAA")
+            .find_position(|&n| n == "ZZZ")
+            .expect("camel got lost");
+        n as i64
+    }
+
+    fn part2(instructions: Instructions) -> i64 {
+        let nodes = instructions.nodes.iter();
+        let path_infos = nodes
+            .map(|node| node.this.as_str())
+            .filter(|name| name.ends_with('A'))
+            .map(|name| PathInfo::compute(&instructions, name))
+            .collect_vec();
+
+        // Before using these fancy path infos to do fancy number theory, we first
+        // just do a brute force iteration up to the point where we're sure the
+        // fancy number theory actually applies.
+        for i in 0..path_infos.iter().map(|pi| pi.end).max().unwrap_or(0) {
+            if path_infos.iter().all(|path_info| path_info.is_end_point(i)) {
+                return i as i64;
+            }
+        }
+
+        // Now the real fanciness begins. Every ghost is at an end node at
+        // certain offsets into its loop, repeating with the length of the
+        // loop. For each way of picking one such offset per ghost, the
+        // Chinese remainder theorem tells us when they coincide, if ever. Any
+        // other failure means we can't trust the minimum.
+        path_infos
+            .iter()
+            .map(PathInfo::sequences)
+            .multi_cartesian_product()
+            .filter_map(|sequences| {
+                let lower = sequences.iter().map(|&(off, _)| off as i128).max()?;
+                let congruences = sequences.iter().map(|&(off, n)| (off as i128, n as i128));
+                match aoc::num::crt(congruences) {
+                    // The solution has to be past all the leads
+                    Ok((x, m)) => Some(Ok(x + (lower - x + m - 1).max(0) / m * m)),
+                    Err(aoc::num::CrtError::NoSolution) => None,
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .process_results(|meetings| meetings.min())
+            .unwrap_or_else(|e| panic!("can't tell when ghosts meet: {e}"))
+            .expect("ghosts never meet") as i64
+    }
 }
 
 // Iterate through the path starting at a certain node, producing nodes
 fn path<'a>(instructions: &'a Instructions, mut name: &'a str) -> impl Iterator<Item = &'a str> {
-    let nodes = instructions.nodes.iter();
-    let tree: HashMap<&str, (&str, &str)> = nodes
-        .map(|Node { this, left, right }| (this.as_str(), (left.as_str(), right.as_str())))
-        .collect();
+    let tree = tree(instructions);
     instructions.turns.iter().cycle().map(move |turn| {
         let out = name;
-        if let Some((left, right)) = tree.get(name) {
-            name = match turn {
-                Turn::R => right,
-                Turn::L => left,
-            };
-        }
+        name = next(&tree, name, turn);
         out
     })
 }
 
+type Tree<'a> = HashMap<&'a str, (&'a str, &'a str)>;
+
+fn tree(instructions: &Instructions) -> Tree<'_> {
+    let nodes = instructions.nodes.iter();
+    nodes
+        .map(|Node { this, left, right }| (this.as_str(), (left.as_str(), right.as_str())))
+        .collect()
+}
+
+// Take a single turn from a node
+fn next<'a>(tree: &Tree<'a>, name: &'a str, turn: &Turn) -> &'a str {
+    match (tree.get(name), turn) {
+        (Some((left, _)), Turn::L) => left,
+        (Some((_, right)), Turn::R) => right,
+        (None, _) => name,
+    }
+}
+
 /// Higher level representation of a path, resulting from an analysis of its
 /// periodicity. The path consists of a "lead" followed by infinite iterations of
 /// a "loop". The first occurence of the loop occupies path indices `start..end`.
@@ -81,29 +146,24 @@ struct PathInfo {
 }
 
 impl PathInfo {
-    /// Compute the `PathInfo` for a given starting node
-    fn compute<'a>(instructions: &'a Instructions, name: &'a str) -> Option<Self> {
-        let path = path(instructions, name);
-        let n_turns = instructions.turns.len();
-        let mut seen: HashMap<&str, usize> = HashMap::new();
-        let mut end_points: HashSet<usize> = HashSet::new();
-        for (i, name) in path.enumerate() {
-            if name.ends_with('Z') {
-                end_points.insert(i);
-            }
-            if let Some(j) = seen.get(name) {
-                if (i - j) % n_turns == 0 {
-                    return Some(PathInfo {
-                        start: *j,
-                        end: i,
-                        end_points,
-                    });
-                }
-            } else {
-                seen.insert(name, i);
-            }
+    /// Compute the `PathInfo` for a given starting node. The ghost's state
+    /// is the node it's at along with how far it is through the turns.
+    fn compute<'a>(instructions: &'a Instructions, name: &'a str) -> Self {
+        let tree = tree(instructions);
+        let turns = &instructions.turns;
+        let cycle = aoc::cycle::find((name, 0), |&(name, i)| {
+            (next(&tree, name, &turns[i]), (i + 1) % turns.len())
+        });
+        let end_points = cycle
+            .states()
+            .iter()
+            .positions(|(name, _)| name.ends_with('Z'))
+            .collect();
+        PathInfo {
+            start: cycle.lead,
+            end: cycle.lead + cycle.period,
+            end_points,
         }
-        None
     }
 
     /// Figure out whether the `i`-th node is an end node
@@ -125,57 +185,3 @@ impl PathInfo {
             .collect_vec()
     }
 }
-
-#[derive(Debug, Clone)]
-struct Sequence {
-    off: usize,
-    period: usize,
-}
-
-fn gcd(mut a: usize, mut b: usize) -> usize {
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-    a
-}
-
-fn lcm(a: usize, b: usize) -> usize {
-    (a / gcd(a, b)) * b
-}
-
-fn solve((off1, period1): (usize, usize), (off2, period2): (usize, usize)) -> (usize, usize) {
-    let i = (0..)
-        .find(|i| (off1 + i * period1) % period2 == off2 % period2)
-        .unwrap();
-    (off1 + i * period1, lcm(period1, period2))
-}
-
-fn part2(instructions: Instructions) -> i64 {
-    let nodes = instructions.nodes.iter();
-    let path_infos = nodes
-        .map(|node| node.this.as_str())
-        .filter(|name| name.ends_with('A'))
-        .flat_map(|name| PathInfo::compute(&instructions, name))
-        .collect_vec();
-
-    // Before using these fancy path infos to do fancy number theory, we first
-    // just do a brute force iteration up to the point where we're sure the
-    // fancy number theory actually applies.
-    for i in 0..path_infos.iter().map(|pi| pi.end).max().unwrap_or(0) {
-        if path_infos.iter().all(|path_info| path_info.is_end_point(i)) {
-            return i as i64;
-        }
-    }
-
-    // Now the real fanciness begins. We retrieve a bunch of arithmetic
-    // progressions and intersect them.
-    let map = path_infos
-        .iter()
-        .map(PathInfo::sequences)
-        .multi_cartesian_product()
-        .flat_map(|sequences| sequences.into_iter().reduce(solve))
-        .map(|(x, _)| x)
-        .min()
-        .unwrap();
-    map as i64
-}
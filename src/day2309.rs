@@ -1,9 +1,7 @@
 use crate::util::*;
 
-aoc_test!(part1, 230901, 114);
-aoc_test!(part1, 230900, 1884768153);
-aoc_test!(part2, 230901, 2);
-aoc_test!(part2, 230900, 1031);
+aoc_register!(Day2309);
+aoc_test!(Day2309);
 
 #[derive(From)]
 struct Oasis {
@@ -26,12 +24,28 @@ impl FromStr for Oasis {
     }
 }
 
-fn part1(oasis: Oasis) -> i64 {
-    oasis.histories.into_iter().map(forecast).sum()
-}
+struct Day2309;
+
+impl Solution for Day2309 {
+    type Input = Oasis;
+    type Output1 = i64;
+    type Output2 = i64;
 
-fn part2(oasis: Oasis) -> i64 {
-    oasis.histories.into_iter().map(hindcast).sum()
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        9
+    }
+
+    fn part1(oasis: Oasis) -> i64 {
+        oasis.histories.into_iter().map(forecast).sum()
+    }
+
+    fn part2(oasis: Oasis) -> i64 {
+        oasis.histories.into_iter().map(hindcast).sum()
+    }
 }
 
 fn differentiate(values: &[i64]) -> Vec<i64> {
@@ -1,22 +1,11 @@
-use std::ops::Range;
-
-use itertools::iterate;
-
 use crate::util::*;
 
-aoc_test!(part1, 231001, 4);
-aoc_test!(part1, 231002, 8);
-aoc_test!(part1, 231000, 6690);
-aoc_test!(part2, 231001, 1);
-aoc_test!(part2, 231003, 4);
-aoc_test!(part2, 231004, 8);
-aoc_test!(part2, 231005, 10);
-
-aoc_test!(part2, 231000, 525);
+aoc_register!(Day2310);
+aoc_test!(Day2310);
 
 #[derive(Debug, From, Clone)]
 struct Maze {
-    tiles: Vec<Vec<Tile>>,
+    tiles: Grid<Tile>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -48,46 +37,32 @@ impl Tile {
 
 use aoc_grid::*;
 
-struct Dim(Range<usize>, Range<usize>);
 impl FromStr for Maze {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use aoc_nom::*;
         use Tile::*;
-        let tile = alt((
-            tag("|").map(|_| NS),
-            tag("-").map(|_| EW),
-            tag("L").map(|_| NE),
-            tag("J").map(|_| NW),
-            tag("7").map(|_| SW),
-            tag("F").map(|_| SE),
-            tag(".").map(|_| No),
-            tag("S").map(|_| Start),
-        ));
-        let maze = separated_list1(multispace1, many1(tile)).map(Maze::from);
-        maze.anyhow(s)
+        let tiles = Grid::parse(s, |c| match c {
+            '|' => Ok(NS),
+            '-' => Ok(EW),
+            'L' => Ok(NE),
+            'J' => Ok(NW),
+            '7' => Ok(SW),
+            'F' => Ok(SE),
+            '.' => Ok(No),
+            'S' => Ok(Start),
+            _ => Err(anyhow!("unknown tile")),
+        })?;
+        Ok(Maze { tiles })
     }
 }
 
 impl Maze {
-    fn dim(&self) -> Pos {
-        Pos(self.tiles.len(), self.tiles[0].len())
-    }
-
     fn find_animal(&mut self) -> Pos {
         let pos = self
             .tiles
-            .iter()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.iter()
-                    .enumerate()
-                    .map(move |(col, tile)| (Pos(row, col), tile))
-            })
-            .find(|(_pos, &tile)| tile == Tile::Start)
-            .expect("no animal")
-            .0;
+            .find(|&tile| tile == Tile::Start)
+            .expect("no animal");
         self.reveal_hidden_tile(pos);
         pos
     }
@@ -95,19 +70,15 @@ impl Maze {
     fn reveal_hidden_tile(&mut self, pos: Pos) {
         use Dir::*;
         use Tile::*;
-        let dim = self.dim();
+        let dim = self.tiles.size();
         let connections = Dir::iter()
             .map(|direction| {
                 pos.walk(direction, dim)
-                    .map(|pos| {
-                        self.at(pos)
-                            .map(|tile| tile.directions().contains(&direction.opposite()))
-                            .unwrap_or(false)
-                    })
+                    .map(|pos| self.tiles[pos].directions().contains(&direction.opposite()))
                     .unwrap_or(false)
             })
             .collect_vec();
-        self.tiles[pos.0][pos.1] = match connections.as_slice() {
+        self.tiles[pos] = match connections.as_slice() {
             // N     E     S      W
             [true, true, false, false] => NE,
             [true, false, true, false] => NS,
@@ -119,18 +90,10 @@ impl Maze {
         };
     }
 
-    fn at(&self, pos: Pos) -> Option<Tile> {
-        let dim = self.dim();
-        if dim.contains(pos) {
-            Some(self.tiles[pos.0][pos.1])
-        } else {
-            None
-        }
-    }
-
     fn walk_from(&self, prev: Pos, pos: Pos) -> (Dir, Pos) {
-        let dim = self.dim();
-        self.at(pos)
+        let dim = self.tiles.size();
+        self.tiles
+            .get(pos)
             .into_iter()
             .flat_map(|tile| {
                 tile.directions()
@@ -157,40 +120,54 @@ impl Maze {
     }
 }
 
-fn part1(mut maze: Maze) -> usize {
-    let (positions, _directions) = maze.path();
-    positions.len() / 2
-}
+struct Day2310;
 
-fn part2(mut maze: Maze) -> usize {
-    let (positions, _) = maze.path();
+impl Solution for Day2310 {
+    type Input = Maze;
+    type Output1 = usize;
+    type Output2 = usize;
 
-    // Clear out all the plumbing that doesn't belong to the cycle
-    let boundary = positions.into_iter().collect::<HashSet<_>>();
-    for (row, line) in maze.tiles.iter_mut().enumerate() {
-        for (col, tile) in line.iter_mut().enumerate() {
-            if !boundary.contains(&Pos(row, col)) {
-                *tile = Tile::No;
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        10
+    }
+
+    fn part1(mut maze: Maze) -> usize {
+        let (positions, _directions) = maze.path();
+        positions.len() / 2
+    }
+
+    fn part2(mut maze: Maze) -> usize {
+        let (positions, _) = maze.path();
+
+        // Clear out all the plumbing that doesn't belong to the cycle
+        let boundary = positions.into_iter().collect::<HashSet<_>>();
+        for pos in maze.tiles.positions() {
+            if !boundary.contains(&pos) {
+                maze.tiles[pos] = Tile::No;
             }
         }
-    }
 
-    use Tile::*;
-    let mut c = 0; // Number of interior tiles encountered so far
-    let mut interior = false; // Are we currently inside the loop?
-    let mut latest_ne = false; // Have we seen NE more recently than SE?
-    for row in maze.tiles {
-        for tile in row {
-            match (tile, interior, latest_ne) {
-                (No, true, _) => c += 1,
-                (NE, _, _) => latest_ne = true,
-                (SE, _, _) => latest_ne = false,
-                (NS, _, _) => interior = !interior,
-                (SW, _, true) => interior = !interior,
-                (NW, _, false) => interior = !interior,
-                _ => {}
+        use Tile::*;
+        let mut c = 0; // Number of interior tiles encountered so far
+        let mut interior = false; // Are we currently inside the loop?
+        let mut latest_ne = false; // Have we seen NE more recently than SE?
+        for row in maze.tiles.rows() {
+            for &tile in row {
+                match (tile, interior, latest_ne) {
+                    (No, true, _) => c += 1,
+                    (NE, _, _) => latest_ne = true,
+                    (SE, _, _) => latest_ne = false,
+                    (NS, _, _) => interior = !interior,
+                    (SW, _, true) => interior = !interior,
+                    (NW, _, false) => interior = !interior,
+                    _ => {}
+                }
             }
         }
+        c
     }
-    c
 }
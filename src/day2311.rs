@@ -1,28 +1,47 @@
-use ndarray::Array2;
-
 use crate::util::*;
 
-aoc_test!(expand_2, 231101, 374);
-aoc_test!(expand_2, 231100, 9734203);
+use aoc_grid::*;
+
+aoc_register!(Day2311);
+aoc_test!(Day2311);
 aoc_test!(expand_1e1, 231101, 1030);
 aoc_test!(expand_1e2, 231101, 8410);
-aoc_test!(expand_1e6, 231100, 568914596391);
 
 struct Chart {
-    galaxies: Array2<bool>,
+    galaxies: Grid<bool>,
 }
 
 impl FromStr for Chart {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let galaxies = aoc::array2(s.lines().map(|line| line.chars().map(|c| c == '#')))?;
+        let galaxies = Grid::parse(s, |c| Ok(c == '#'))?;
         Ok(Chart { galaxies })
     }
 }
 
-fn expand_2(chart: Chart) -> usize {
-    compute(chart, 2)
+struct Day2311;
+
+impl Solution for Day2311 {
+    type Input = Chart;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        11
+    }
+
+    fn part1(chart: Chart) -> usize {
+        compute(chart, 2)
+    }
+
+    fn part2(chart: Chart) -> usize {
+        compute(chart, 1000000)
+    }
 }
 
 fn expand_1e1(chart: Chart) -> usize {
@@ -33,10 +52,6 @@ fn expand_1e2(chart: Chart) -> usize {
     compute(chart, 100)
 }
 
-fn expand_1e6(chart: Chart) -> usize {
-    compute(chart, 1000000)
-}
-
 // Solve the problem with a universe expansion factor of `k`.
 fn compute(Chart { galaxies }: Chart, k: usize) -> usize {
     // Gather up the indices of empty rows and columns into ordered sets.
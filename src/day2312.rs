@@ -5,19 +5,31 @@ use test_case::test_case;
 
 use crate::util::*;
 
-aoc_test!(part1, 231201, 21);
-aoc_test!(part1, 231200, 7251);
-aoc_test!(part2, 231201, 525152);
+aoc_register!(Day2312);
+aoc_test!(Day2312, slow);
 
-// SLOW TEST
-// aoc_test!(part2, 231200, 2128386729962);
+struct Day2312;
 
-fn part1(records: Records) -> usize {
-    records.count_matches()
-}
+impl Solution for Day2312 {
+    type Input = Records;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        12
+    }
+
+    fn part1(records: Records) -> usize {
+        records.count_matches()
+    }
 
-fn part2(records: Records) -> usize {
-    records.count_extended_matches()
+    fn part2(records: Records) -> usize {
+        records.count_extended_matches()
+    }
 }
 
 struct Records(Vec<Record>);
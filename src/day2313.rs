@@ -1,24 +1,40 @@
-use ndarray::{s, Array2, ArrayView2};
+use ndarray::{s, ArrayView2};
 
 use crate::util::*;
 
-aoc_test!(part1, 231301, 405);
-aoc_test!(part1, 231300, 33735);
-aoc_test!(part2, 231301, 400);
-aoc_test!(part2, 231300, 38063);
+use aoc_grid::*;
+
+aoc_register!(Day2313);
+aoc_test!(Day2313);
 
 #[derive(Debug)]
 struct Patterns(Vec<Pattern>);
 
 #[derive(Debug)]
-struct Pattern(Array2<bool>);
+struct Pattern(Grid<bool>);
 
-fn part1(patterns: Patterns) -> usize {
-    patterns.summary(0)
-}
+struct Day2313;
+
+impl Solution for Day2313 {
+    type Input = Patterns;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2023
+    }
 
-fn part2(patterns: Patterns) -> usize {
-    patterns.summary(1)
+    fn day() -> u8 {
+        13
+    }
+
+    fn part1(patterns: Patterns) -> usize {
+        patterns.summary(0)
+    }
+
+    fn part2(patterns: Patterns) -> usize {
+        patterns.summary(1)
+    }
 }
 
 impl Patterns {
@@ -66,13 +82,7 @@ impl FromStr for Patterns {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let patterns = s
             .split("\n\n")
-            .map(|pattern| {
-                let cs = pattern
-                    .trim()
-                    .lines()
-                    .map(|line| line.chars().map(|c| c == '#'));
-                Ok(Pattern(aoc::array2(cs)?))
-            })
+            .map(|pattern| Ok(Pattern(Grid::parse(pattern.trim(), |c| Ok(c == '#'))?)))
             .collect::<anyhow::Result<_>>()?;
         Ok(Patterns(patterns))
     }
@@ -1,9 +1,11 @@
-use ndarray::{s, Array2, ArrayViewMut2};
+use ndarray::{s, ArrayViewMut2};
 
 use crate::util::*;
 
+use aoc_grid::*;
+
 #[derive(Hash, Clone, Eq, PartialEq)]
-struct Platform(Array2<Tile>);
+struct Platform(Grid<Tile>);
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 enum Tile {
@@ -12,37 +14,37 @@ enum Tile {
     Empty,
 }
 
-aoc_test!(part1, 231401, 136);
-aoc_test!(part1, 231400, 113486);
-aoc_test!(part2, 231401, 64);
-aoc_test!(part2, 231400, 104409);
+aoc_register!(Day2314);
+aoc_test!(Day2314);
 
-fn part1(mut platform: Platform) -> usize {
-    platform.roll();
-    platform.total_load()
-}
+struct Day2314;
 
-fn part2(mut platform: Platform) -> usize {
-    let mut i_by_platform = HashMap::new();
-    let mut i = 0;
-    let n = 1000000000;
-    while i < n {
-        platform.cycle();
-        if let Some(j) = i_by_platform.insert(platform.clone(), i) {
-            // We have detected a cycle, and may as well fast forward.
-            i = n - (n - i) % (i - j) + 1;
-        } else {
-            i += 1;
-        }
+impl Solution for Day2314 {
+    type Input = Platform;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2023
     }
-    platform.total_load()
-}
 
-enum Dir {
-    N,
-    W,
-    S,
-    E,
+    fn day() -> u8 {
+        14
+    }
+
+    fn part1(mut platform: Platform) -> usize {
+        platform.roll();
+        platform.total_load()
+    }
+
+    fn part2(platform: Platform) -> usize {
+        let cycle = aoc::cycle::find(platform, |platform| {
+            let mut platform = platform.clone();
+            platform.cycle();
+            platform
+        });
+        cycle.nth(1000000000).total_load()
+    }
 }
 
 impl Platform {
@@ -57,7 +59,7 @@ impl Platform {
     }
 
     /// View the array of tiles from a given direction
-    fn view(&mut self, dir: Dir) -> ArrayViewMut2<Tile> {
+    fn view(&mut self, dir: Dir) -> ArrayViewMut2<'_, Tile> {
         match dir {
             Dir::N => self.0.slice_mut(s![.., ..]),
             Dir::W => self.0.slice_mut(s![.., ..]).reversed_axes(),
@@ -99,16 +101,20 @@ impl FromStr for Platform {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cs = s.lines().map(|line| {
-            line.chars().map(|c| match c {
-                'O' => Tile::Round,
-                '#' => Tile::Cube,
-                '.' => Tile::Empty,
-                _ => panic!(),
-            })
-        });
-        let grid = aoc::array2(cs);
-        Ok(Platform(grid?))
+        Ok(Platform(s.parse()?))
+    }
+}
+
+impl TryFrom<char> for Tile {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'O' => Ok(Tile::Round),
+            '#' => Ok(Tile::Cube),
+            '.' => Ok(Tile::Empty),
+            _ => Err(anyhow!("unknown tile")),
+        }
     }
 }
 
@@ -128,12 +134,6 @@ impl Display for Tile {
 
 impl Display for Platform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.0.rows() {
-            for tile in row.into_iter() {
-                write!(f, "{}", tile)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        write!(f, "{}", self.0)
     }
 }
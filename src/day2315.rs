@@ -1,12 +1,35 @@
 use crate::util::*;
 
-aoc_test!(part1, 231501, 1320);
-aoc_test!(part1, 231500, 494980);
-aoc_test!(part2, 231501, 145);
-aoc_test!(part2, 231500, 247933);
+aoc_register!(Day2315);
+aoc_test!(Day2315);
 
-fn part1(sequence: String) -> usize {
-    sequence.trim().split(',').map(hash).sum()
+struct Day2315;
+
+impl Solution for Day2315 {
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn year() -> u16 {
+        2023
+    }
+
+    fn day() -> u8 {
+        15
+    }
+
+    /// Hashes the steps as they are, so this doesn't care whether they make
+    /// sense as instructions
+    fn part1(sequence: String) -> usize {
+        sequence.trim().split(',').map(hash).sum()
+    }
+
+    fn part2(sequence: String) -> usize {
+        let instructions: Instructions = sequence.parse().expect("invalid instructions");
+        let mut bins = Bins::new();
+        bins.run_all(instructions);
+        bins.power()
+    }
 }
 
 fn hash(s: &str) -> usize {
@@ -15,12 +38,6 @@ fn hash(s: &str) -> usize {
         .fold(0, |h: u8, &c| h.wrapping_add(c).wrapping_mul(17)) as usize
 }
 
-fn part2(instructions: Instructions) -> usize {
-    let mut bins = Bins::new();
-    bins.run_all(instructions);
-    bins.power()
-}
-
 #[derive(Deref, DerefMut)]
 struct Instructions(Vec<Instruction>);
 
@@ -108,7 +125,27 @@ impl FromStr for Instructions {
         let instruction = tuple((alpha1, alt((remove, insert))));
         let instruction = instruction.map(|(s, op)| Instruction(Key(String::from(s)), op));
         let instructions = separated_list1(tag(","), instruction).map(Instructions);
-        let instructions = terminated(instructions, eof);
         instructions.anyhow(s)
     }
 }
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Instruction(Key(key), operation) = self;
+        match operation {
+            Operation::Remove => write!(f, "{key}-"),
+            Operation::Insert(Val(val)) => write!(f, "{key}={val}"),
+        }
+    }
+}
+
+#[test]
+fn test_raw_steps() {
+    assert_eq!(Day2315::part1("HASH\n".to_string()), 52);
+    // Part 2 would choke on these, but part 1 hashes them all the same
+    assert_eq!(
+        Day2315::part1("ab=07,x!".to_string()),
+        hash("ab=07") + hash("x!")
+    );
+    assert_ne!(hash("ab=07"), hash("ab=7"));
+}
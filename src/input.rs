@@ -0,0 +1,190 @@
+//! Loading puzzle input from files, with errors that say exactly where things
+//! went wrong: which file, and for parsers that can tell us, which line and
+//! column.
+
+use std::path::{Path, PathBuf};
+
+use crate::util::aoc_nom::ParseError;
+use crate::util::*;
+
+/// Something went wrong while loading puzzle input.
+#[derive(Debug)]
+pub enum InputError {
+    /// The file couldn't be read at all.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file was read, but its contents couldn't be parsed. If the parser
+    /// reported a position, `location` points at it.
+    Parse {
+        path: PathBuf,
+        source: anyhow::Error,
+        location: Option<Location>,
+    },
+}
+
+/// A position in a text, along with the line it occurs on, for reporting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Line number, counting from 1
+    pub line: usize,
+    /// Column number in characters, counting from 1
+    pub column: usize,
+    /// The full text of the line
+    pub text: String,
+}
+
+/// Load the input file `"input/{input_number}.txt"` and parse it.
+pub fn load_input<I>(input_number: u32) -> Result<I, InputError>
+where
+    I: FromStr,
+    I::Err: Into<anyhow::Error>,
+{
+    load_input_from(format!("input/{input_number}.txt"))
+}
+
+/// Load any input file and parse it.
+pub fn load_input_from<I>(path: impl AsRef<Path>) -> Result<I, InputError>
+where
+    I: FromStr,
+    I::Err: Into<anyhow::Error>,
+{
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|source| InputError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    text.parse()
+        .map_err(|e: I::Err| InputError::parse(path, &text, e.into()))
+}
+
+impl InputError {
+    /// Wrap up an error that occurred while parsing `text`, which was read
+    /// from `path`. If the error came from one of our nom parsers, we work out
+    /// where in `text` it occurred.
+    pub fn parse(path: impl AsRef<Path>, text: &str, mut source: anyhow::Error) -> Self {
+        if let Some(e) = source.downcast_mut::<ParseError>() {
+            e.relocate(text);
+        }
+        let location = source
+            .chain()
+            .find_map(|e| e.downcast_ref::<ParseError>())
+            .and_then(|e| e.location().cloned());
+        InputError::Parse {
+            path: path.as_ref().to_path_buf(),
+            source,
+            location,
+        }
+    }
+}
+
+impl Location {
+    /// Find the line and column of byte `offset` in `text`.
+    pub fn of(text: &str, offset: usize) -> Self {
+        let offset = offset.min(text.len());
+        let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+        Location {
+            line: text[..start].matches('\n').count() + 1,
+            column: text[start..offset].chars().count() + 1,
+            text: text[start..end].trim_end_matches('\r').to_string(),
+        }
+    }
+
+    /// Render the line with a caret under the column, like a compiler would.
+    /// Very long lines are cut down to the part around the column.
+    fn render(&self, f: &mut std::fmt::Formatter<'_>, path: &Path) -> std::fmt::Result {
+        const CONTEXT: usize = 40;
+        let Location { line, column, text } = self;
+        let skip = column.saturating_sub(CONTEXT + 1);
+        let shown: String = text.chars().skip(skip).take(2 * CONTEXT).collect();
+        let (prefix, caret) = if skip > 0 {
+            ("...", column - skip + 3)
+        } else {
+            ("", *column)
+        };
+        let gutter = " ".repeat(line.to_string().len());
+        writeln!(f, "{gutter}--> {}:{line}:{column}", path.display())?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line} | {prefix}{shown}")?;
+        write!(f, "{gutter} | {}^", " ".repeat(caret - 1))
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Io { path, source } => {
+                write!(f, "cannot read {}: {source}", path.display())
+            }
+            InputError::Parse {
+                path,
+                source,
+                location: None,
+            } => write!(f, "cannot parse {}: {source:#}", path.display()),
+            InputError::Parse {
+                path,
+                source,
+                location: Some(location),
+            } => {
+                writeln!(f, "cannot parse {}", path.display())?;
+                location.render(f, path)?;
+                write!(f, " {source:#}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputError::Io { source, .. } => Some(source),
+            InputError::Parse { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::aoc_nom::*;
+
+    #[test]
+    fn locate_error_in_line() {
+        let text = "12\n34\n5x6\n";
+        let error = text
+            .lines()
+            .map(|line| u32.into_str_parser().anyhow(line))
+            .find_map(Result::err)
+            .expect("parse error");
+        let error = InputError::parse("input.txt", text, error);
+        let InputError::Parse { location, .. } = &error else {
+            panic!("not a parse error")
+        };
+        let location = location.as_ref().expect("no location");
+        assert_eq!((location.line, location.column), (3, 2));
+        assert_eq!(location.text, "5x6");
+        assert_eq!(
+            error.to_string(),
+            "cannot parse input.txt
+ --> input.txt:3:2
+  |
+3 | 5x6
+  |  ^ unexpected trailing input at \"x6\" (line 3, column 2)"
+        );
+    }
+    #[test]
+    fn locate_error_elsewhere() {
+        use nom::error::{VerboseError, VerboseErrorKind};
+        /// A parser that blames some text other than its input
+        fn elsewhere(_: &str) -> nom::IResult<&str, (), VerboseError<&str>> {
+            let errors = vec![("somewhere else entirely", VerboseErrorKind::Context("x"))];
+            Err(nom::Err::Error(VerboseError { errors }))
+        }
+        let error = elsewhere.anyhow("ab\ncd").unwrap_err();
+        let error = error.downcast_ref::<ParseError>().expect("parse error");
+        let location = error.location().expect("no location");
+        assert_eq!((location.line, location.column), (2, 3));
+    }
+}
@@ -10,6 +10,11 @@ pub use std;
 
 mod util;
 
+pub mod bench;
+pub mod input;
+pub mod manifest;
+pub mod registry;
+
 mod day1501;
 mod day1502;
 mod day1503;
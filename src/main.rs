@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::anyhow;
+use aoc::input::InputError;
+use aoc::{bench, registry};
+use clap::{Parser, Subcommand};
+
+/// Run Advent of Code solutions from the command line.
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a solution on some input and print the answer
+    Run {
+        year: u16,
+        day: u8,
+        /// Which part to run; both parts are run if this is omitted
+        #[arg(long)]
+        part: Option<u8>,
+        /// The input file; defaults to the real puzzle input in `input/`
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+    /// List all the registered solutions
+    List,
+    /// Time solutions on their real puzzle input and write a JSON report
+    Bench {
+        /// Only benchmark solutions from this year
+        year: Option<u16>,
+        /// Only benchmark solutions for this day
+        day: Option<u8>,
+        /// Only benchmark this part
+        #[arg(long)]
+        part: Option<u8>,
+        /// How many times to run each part
+        #[arg(long, default_value_t = 10)]
+        runs: usize,
+        /// Where to write the JSON report
+        #[arg(long, default_value = "bench.json")]
+        output: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    // Errors are printed with `Display` rather than `Debug`, since that's what
+    // renders the location of parse errors nicely.
+    match dispatch(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn dispatch(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Run {
+            year,
+            day,
+            part,
+            input,
+        } => run(year, day, part, input),
+        Command::List => {
+            list();
+            Ok(())
+        }
+        Command::Bench {
+            year,
+            day,
+            part,
+            runs,
+            output,
+        } => bench(year, day, part, runs, output),
+    }
+}
+
+fn run(year: u16, day: u8, part: Option<u8>, input: Option<PathBuf>) -> anyhow::Result<()> {
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    for part in parts {
+        let solution = registry::find(year, day, part)
+            .ok_or_else(|| anyhow!("no solution for {year} day {day} part {part}"))?;
+        let path = input
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(solution.input_path()));
+        let text = std::fs::read_to_string(&path).map_err(|source| InputError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let answer = (solution.run)(&text).map_err(|e| InputError::parse(&path, &text, e))?;
+        println!("{answer}");
+    }
+    Ok(())
+}
+
+fn list() {
+    for part in registry::all() {
+        println!("{part}");
+    }
+}
+
+fn bench(
+    year: Option<u16>,
+    day: Option<u8>,
+    part: Option<u8>,
+    runs: usize,
+    output: PathBuf,
+) -> anyhow::Result<()> {
+    let selected = registry::all().filter(|p| {
+        year.is_none_or(|year| p.year == year)
+            && day.is_none_or(|day| p.day == day)
+            && part.is_none_or(|part| p.part == part)
+    });
+    let mut reports = vec![];
+    for solution in selected {
+        let path = solution.input_path();
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            eprintln!("skipping {solution}: cannot read {path}");
+            continue;
+        };
+        // A solution that panics shouldn't stop us from benchmarking all
+        // the others. We report the panic ourselves, so the default hook
+        // needn't print it too.
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let report = std::panic::catch_unwind(|| bench::bench(&solution, &text, runs));
+        std::panic::set_hook(hook);
+        match report {
+            Ok(Ok(report)) => {
+                println!("{report}");
+                reports.push(report);
+            }
+            Ok(Err(e)) => eprintln!("skipping {solution}: {e:#}"),
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("no message");
+                eprintln!("skipping {solution}: panicked: {message}")
+            }
+        }
+    }
+    let file = std::fs::File::create(&output)
+        .map_err(|e| anyhow!("cannot create {}: {e}", output.display()))?;
+    serde_json::to_writer_pretty(file, &reports)?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Each year has a manifest, `input/{year}.toml`, that lists the input files
+//! for that year along with the answers we expect for them. Adding a test case
+//! is a matter of adding an input file and an entry to the manifest.
+
+use serde::Deserialize;
+
+use crate::util::*;
+
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "input")]
+    pub inputs: Vec<Input>,
+}
+
+/// One input file, and the expected answers for whichever parts we know them.
+#[derive(Debug, Deserialize)]
+pub struct Input {
+    pub id: u32,
+    pub description: String,
+    pub part1: Option<Expected>,
+    pub part2: Option<Expected>,
+    /// Parts that take too long to check on every test run.
+    #[serde(default)]
+    pub slow: Vec<u8>,
+}
+
+/// An expected answer. Most are numbers, but some are text.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Expected {
+    Number(i64),
+    Text(String),
+}
+
+impl Manifest {
+    pub fn load(year: u16) -> anyhow::Result<Self> {
+        let path = format!("input/{year}.toml");
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("cannot read manifest {path}: {e}"))?;
+        let manifest = toml::from_str(&text)?;
+        Ok(manifest)
+    }
+
+    /// The inputs for a given day.
+    pub fn inputs(&self, day: u8) -> impl Iterator<Item = &Input> {
+        self.inputs.iter().filter(move |input| input.day() == day)
+    }
+}
+
+impl Input {
+    pub fn day(&self) -> u8 {
+        (self.id / 100 % 100) as u8
+    }
+
+    pub fn path(&self) -> String {
+        format!("input/{}.txt", self.id)
+    }
+
+    pub fn expected(&self, part: u8) -> Option<&Expected> {
+        match part {
+            1 => self.part1.as_ref(),
+            2 => self.part2.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn is_slow(&self, part: u8) -> bool {
+        self.slow.contains(&part)
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expected::Number(n) => write!(f, "{n}"),
+            Expected::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
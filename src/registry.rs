@@ -0,0 +1,104 @@
+//! Every day implements `Solution` and registers itself here, so that it can
+//! be looked up by year, day and part, and run on arbitrary input without the
+//! caller having to know the input and output types involved.
+
+use crate::bench::Timing;
+use crate::util::*;
+
+/// A solution to both parts of one day's puzzle.
+pub trait Solution {
+    /// The parsed puzzle input, which is shared by both parts.
+    type Input: FromStr<Err: Into<anyhow::Error>>;
+    type Output1: Display + 'static;
+    type Output2: Display + 'static;
+
+    fn year() -> u16;
+    fn day() -> u8;
+    fn part1(input: Self::Input) -> Self::Output1;
+    fn part2(input: Self::Input) -> Self::Output2;
+
+    /// Parse the puzzle input, turning whatever error `FromStr` produces into
+    /// an `anyhow::Error`.
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        input.parse().map_err(Into::into)
+    }
+}
+
+/// The answer to some part of some puzzle. All we know about it is that it can
+/// be displayed.
+pub type Answer = Box<dyn Display>;
+
+/// A type-erased entry point: parse the input, solve the puzzle, and produce
+/// an answer.
+pub type Runner = fn(&str) -> anyhow::Result<Answer>;
+
+/// One part of one day's puzzle.
+#[derive(Clone, Copy)]
+pub struct Part {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub run: Runner,
+    /// Like `run`, but reports how long parsing and solving took instead.
+    pub time: fn(&str) -> anyhow::Result<Timing>,
+}
+
+impl Part {
+    /// Where the real puzzle input for this part lives, by convention.
+    pub fn input_path(&self) -> String {
+        format!("input/{:02}{:02}00.txt", self.year % 100, self.day)
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} day {:2} part {}", self.year, self.day, self.part)
+    }
+}
+
+/// A `Solution` that has been submitted to the inventory, usually by way of
+/// the `aoc_register!` macro.
+pub struct Registration {
+    parts: fn() -> [Part; 2],
+}
+
+impl Registration {
+    pub const fn of<S: Solution>() -> Self {
+        Registration { parts: parts::<S> }
+    }
+}
+
+inventory::collect!(Registration);
+
+fn parts<S: Solution>() -> [Part; 2] {
+    let (year, day) = (S::year(), S::day());
+    [
+        Part {
+            year,
+            day,
+            part: 1,
+            run: |input| Ok(Box::new(S::part1(S::parse(input)?))),
+            time: |input| Timing::measure::<S>(1, input),
+        },
+        Part {
+            year,
+            day,
+            part: 2,
+            run: |input| Ok(Box::new(S::part2(S::parse(input)?))),
+            time: |input| Timing::measure::<S>(2, input),
+        },
+    ]
+}
+
+/// All registered parts, in chronological order.
+pub fn all() -> impl Iterator<Item = Part> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .flat_map(|registration| (registration.parts)())
+        .sorted_by_key(|part| (part.year, part.day, part.part))
+}
+
+/// Look up a specific part of a specific day.
+pub fn find(year: u16, day: u8, part: u8) -> Option<Part> {
+    all().find(|p| (p.year, p.day, p.part) == (year, day, part))
+}
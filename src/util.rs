@@ -1,3 +1,6 @@
+pub use crate::input::load_input;
+pub use crate::manifest;
+pub use crate::registry::Solution;
 pub use anyhow::anyhow;
 pub use derive_more::{Deref, DerefMut, From, Into};
 pub use itertools::Itertools;
@@ -8,66 +11,199 @@ pub use std::str::FromStr;
 pub use strum::EnumIter;
 pub use strum::IntoEnumIterator as _;
 
-/// Create a sensibly named test case that invokes `aoc_run_test`. For instance,
-/// `aoc_test!(part1, 230101, 10)` would expand to:
+/// Create test cases for a `Solution` from the manifest for its year, or a
+/// single sensibly named test case for a free function. For instance,
+/// `aoc_test!(Day2301)` would expand to:
 ///
 /// ```notest
 /// #[test]
-/// fn test_part1_230101() {
-///     aoc_run_test(part1, 230101, 10);
+/// fn test_manifest() {
+///     aoc_run_test::<Day2301>(SlowParts::Forbidden);
 /// }
 /// ```
+///
+/// A day whose manifest marks some parts as slow has to say so, and gets a
+/// separate ignored test for those parts: `aoc_test!(Day2312, slow)` would
+/// expand to:
+///
+/// ```notest
+/// #[test]
+/// fn test_manifest() {
+///     aoc_run_test::<Day2312>(SlowParts::Skipped);
+/// }
+///
+/// #[test]
+/// #[ignore = "slow"]
+/// fn test_manifest_slow() {
+///     aoc_run_test::<Day2312>(SlowParts::Only);
+/// }
+/// ```
+///
+/// while `aoc_test!(part1, 230101, 10)` would expand to:
+///
+/// ```notest
+/// #[test]
+/// fn test_230101_part1() {
+///     aoc_run_test_case(part1, 230101, 10);
+/// }
+/// ```
+///
+/// The manifest is only read when the test runs, so all of a day's inputs
+/// share the one `test_manifest` test, and can't be picked out by test name.
+/// Failures do name the input, though, and setting `AOC_INPUT` to an input
+/// id (like `AOC_INPUT=230101`) only checks that input.
 macro_rules! aoc_test {
-        ($part:ident, $input:expr, $val:expr) => {
-            concat_idents::concat_idents!(test_name = test_, $input, _, $part {
-                #[test]
-                fn test_name() {
-                    aoc_run_test($part, $input, $val);
-                }
-            });
-        };
-    }
+    ($solution:ident) => {
+        #[test]
+        fn test_manifest() {
+            aoc_run_test::<$solution>(SlowParts::Forbidden);
+        }
+    };
+    ($solution:ident, slow) => {
+        #[test]
+        fn test_manifest() {
+            aoc_run_test::<$solution>(SlowParts::Skipped);
+        }
+
+        #[test]
+        #[ignore = "slow"]
+        fn test_manifest_slow() {
+            aoc_run_test::<$solution>(SlowParts::Only);
+        }
+    };
+    ($part:ident, $input:expr, $val:expr) => {
+        concat_idents::concat_idents!(test_name = test_, $input, _, $part {
+            #[test]
+            fn test_name() {
+                aoc_run_test_case($part, $input, $val);
+            }
+        });
+    };
+}
 pub(crate) use aoc_test;
 
+/// Submit a `Solution` to the `registry`, so that it can be found and run by
+/// the `aoc` binary. For instance, `aoc_register!(Day2301)` would expand to:
+///
+/// ```notest
+/// inventory::submit!(registry::Registration::of::<Day2301>());
+/// ```
+macro_rules! aoc_register {
+    ($solution:ident) => {
+        inventory::submit!(crate::registry::Registration::of::<$solution>());
+    };
+}
+pub(crate) use aoc_register;
+
+/// This function, usually invoked by way of the `aoc_test!` macro, will look
+/// up all the inputs for solution `S` in the manifest for its year, and for
+/// each of them:
+///
+/// 1. Read the input file `"input/{id}.txt"`
+/// 2. Parse the input file into `S::Input`
+/// 3. Run whichever parts have an expected answer in the manifest
+/// 4. Assert that the answers, when displayed, match the expected ones
+///
+/// What happens to parts that the manifest marks as slow depends on `slow`.
+/// If `AOC_INPUT` is set, only the input with that id is checked.
+pub fn aoc_run_test<S: Solution>(slow: SlowParts) {
+    let manifest = manifest::Manifest::load(S::year()).expect("cannot read manifest");
+    let only: Option<u32> = std::env::var("AOC_INPUT")
+        .ok()
+        .map(|id| id.parse().expect("AOC_INPUT should be an input id"));
+    let mut checked = 0;
+    let mut failures = vec![];
+    let inputs = manifest.inputs(S::day());
+    for input in inputs.filter(|input| only.is_none_or(|id| input.id == id)) {
+        for part in [1, 2] {
+            let Some(expected) = input.expected(part) else {
+                continue;
+            };
+            match (slow, input.is_slow(part)) {
+                (SlowParts::Forbidden, true) => panic!(
+                    "{} part {part} is marked as slow, so its day needs aoc_test!(_, slow)",
+                    input.id
+                ),
+                (SlowParts::Skipped, true) | (SlowParts::Only, false) => continue,
+                _ => {}
+            }
+            let parsed: S::Input = parse_test_file(input.id);
+            let answer = match part {
+                1 => S::part1(parsed).to_string(),
+                _ => S::part2(parsed).to_string(),
+            };
+            let expected = expected.to_string();
+            if answer != expected {
+                failures.push(format!(
+                    "{} ({}) part {part}: expected {expected}, got {answer}",
+                    input.id, input.description
+                ));
+            }
+            checked += 1;
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    if only.is_none() {
+        assert!(checked > 0, "no inputs in the manifest");
+    }
+}
+
+/// How `aoc_run_test` treats parts that the manifest marks as slow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowParts {
+    /// There shouldn't be any, so fail if there are
+    Forbidden,
+    /// Leave them to a separate, ignored test
+    Skipped,
+    /// Check only those
+    Only,
+}
+
 /// This function, usually invoked by way of the `aoc_test!` macro, will:
 ///
 /// 1. Read the input file `"input/{input_number}.txt"`
 /// 2. Parse the input file into some type `T:FromStr` accepted by `process`
 /// 3. Run the `process` function on the parsed input
 /// 4. Assert that the result of the `process` function is equal to the `expected` value
-pub fn aoc_run_test<I, T>(process: impl Fn(I) -> T, input_number: u32, expected: T)
+pub fn aoc_run_test_case<I, T>(process: impl Fn(I) -> T, input_number: u32, expected: T)
 where
     I: FromStr,
-    I::Err: Debug,
+    I::Err: Into<anyhow::Error>,
     T: Eq + Debug,
 {
     let input = parse_test_file(input_number);
     assert_eq!(process(input), expected);
 }
 
+/// Load and parse an input file for a test, panicking with a readable error
+/// message if that doesn't work out.
 pub fn parse_test_file<I>(input_number: u32) -> I
 where
     I: FromStr,
-    I::Err: Debug,
+    I::Err: Into<anyhow::Error>,
 {
-    let input_path = format!("input/{}.txt", input_number);
-    let input_string = std::fs::read_to_string(input_path).expect("cannot read test data");
-    input_string.parse().expect("cannot parse test data")
+    load_input(input_number).unwrap_or_else(|e| panic!("{e}"))
 }
 
 /// This module has everything needed for parsing AOC input files with the nom
 /// crate. That includes most of the common combinators used for parsing a
 /// `&str` input, as well as some convenience features.
 pub mod aoc_nom {
-    use nom::error::Error;
+    use crate::input::Location;
+    pub use nom::error::context;
+    use nom::error::{VerboseError, VerboseErrorKind};
+    pub use nom::Parser;
     pub use nom::{
         branch::*, bytes::complete::*, character::complete::*, combinator::*, multi::*, sequence::*,
     };
-    pub use nom::{IResult, Parser};
+
+    /// Like nom's own `IResult`, but with the error type that `StrParser`
+    /// expects, which keeps track of the context of every error.
+    pub type IResult<I, O> = nom::IResult<I, O, VerboseError<I>>;
 
     /// Extension trait that is auto-implemented for any parser that operates on
     /// `&str` input.
-    pub trait StrParser<'a, O>: Parser<&'a str, O, Error<&'a str>> {
+    pub trait StrParser<'a, O>: Parser<&'a str, O, VerboseError<&'a str>> {
         /// Specify that this `Parser` is a `StrParser`. This sometimes helps
         /// the type checker be less confused.
         fn into_str_parser(self) -> Self
@@ -85,26 +221,135 @@ pub mod aoc_nom {
         }
 
         /// Use this to eventually actually apply a complete parser to an input
-        /// string. It converts the error type into one that doesn't contain
-        /// references into the input, making it suitable for implementing
-        /// `FromStr`, which doesn't allow such shenanigans.
+        /// string. The parser has to consume all of the input, except perhaps
+        /// for trailing whitespace. It converts the error type into a
+        /// `ParseError`, which doesn't contain references into the input,
+        /// making it suitable for implementing `FromStr`, which doesn't allow
+        /// such shenanigans.
         fn anyhow(mut self, s: &'a str) -> anyhow::Result<O>
         where
             Self: Sized,
         {
-            let result = self.parse(s);
-            let result = result.map_err(|e| e.to_owned());
-            let result = result.map(|(_, o)| o)?;
-            Ok(result)
+            match self.parse(s) {
+                Ok((rest, o)) if rest.trim().is_empty() => Ok(o),
+                Ok((rest, _)) => Err(ParseError::trailing(s, rest).into()),
+                Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(ParseError::new(s, e).into()),
+                Err(nom::Err::Incomplete(_)) => Err(anyhow::anyhow!("incomplete input")),
+            }
+        }
+    }
+
+    impl<'a, O, T> StrParser<'a, O> for T where T: Parser<&'a str, O, VerboseError<&'a str>> {}
+
+    /// The error produced by `StrParser::anyhow`, which records where in the
+    /// input the parser gave up, and what it was in the middle of parsing at
+    /// the time.
+    #[derive(Debug)]
+    pub struct ParseError {
+        /// What went wrong where, innermost first. Positions are relative to
+        /// the string that was parsed.
+        frames: Vec<Frame>,
+    }
+
+    #[derive(Debug)]
+    struct Frame {
+        what: String,
+        location: Location,
+        /// The start of the unparsed input, up to the end of its line
+        snippet: String,
+        /// The address in memory of the unparsed input. If the parsed string
+        /// was itself sliced out of a larger text, for instance by
+        /// `str::lines`, this lets us find the error in that text too.
+        addr: usize,
+    }
+
+    impl ParseError {
+        fn new(s: &str, e: VerboseError<&str>) -> Self {
+            let frames = e.errors.into_iter().map(|(rest, kind)| {
+                let what = match kind {
+                    VerboseErrorKind::Context(context) => format!("while parsing {context}"),
+                    VerboseErrorKind::Char(c) => format!("expected {c:?}"),
+                    VerboseErrorKind::Nom(kind) => format!("{} parser failed", kind.description()),
+                };
+                Frame::new(s, rest, what)
+            });
+            ParseError {
+                frames: frames.collect(),
+            }
+        }
+
+        fn trailing(s: &str, rest: &str) -> Self {
+            let frame = Frame::new(s, rest, "unexpected trailing input".to_string());
+            ParseError {
+                frames: vec![frame],
+            }
+        }
+
+        /// Where the error occurred, relative to the string that was parsed.
+        pub fn location(&self) -> Option<&Location> {
+            self.frames.first().map(|frame| &frame.location)
+        }
+
+        /// If the parsed string was sliced out of a larger `text`, make all
+        /// positions relative to `text` instead.
+        pub fn relocate(&mut self, text: &str) {
+            let start = text.as_ptr() as usize;
+            for frame in self.frames.iter_mut() {
+                if (start..=start + text.len()).contains(&frame.addr) {
+                    frame.location = Location::of(text, frame.addr - start);
+                }
+            }
         }
     }
 
-    impl<'a, O, T> StrParser<'a, O> for T where T: Parser<&'a str, O, Error<&'a str>> {}
+    impl Frame {
+        /// A parser usually fails somewhere in the string it was given, but
+        /// nothing forces it to. If it doesn't, we blame the end of `s`.
+        fn new(s: &str, rest: &str, what: String) -> Self {
+            let snippet = rest.lines().next().unwrap_or("");
+            let offset = (rest.as_ptr() as usize)
+                .checked_sub(s.as_ptr() as usize)
+                .filter(|&offset| offset <= s.len() && s.is_char_boundary(offset))
+                .unwrap_or(s.len());
+            Frame {
+                what,
+                location: Location::of(s, offset),
+                snippet: snippet.chars().take(20).collect(),
+                addr: rest.as_ptr() as usize,
+            }
+        }
+    }
+
+    impl std::fmt::Display for ParseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for (i, frame) in self.frames.iter().enumerate() {
+                let Frame { what, snippet, .. } = frame;
+                let Location { line, column, .. } = frame.location;
+                if i > 0 {
+                    write!(f, "\n  ")?;
+                }
+                match snippet.as_str() {
+                    "" => write!(f, "{what} at end of line")?,
+                    snippet => write!(f, "{what} at {snippet:?}")?,
+                }
+                write!(f, " (line {line}, column {column})")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl std::error::Error for ParseError {}
 }
 
 pub mod aoc {
     use itertools::Itertools;
 
+    pub mod cycle;
+    pub mod interval;
+    pub mod num;
+    pub mod ocr;
+    pub mod search;
+
     /// Maybe this is tucked away somewhere inside of `ndarray` already, but for
     /// the life of me, I can't find it. This transforms an iterator of
     /// iterators of `T` to an `Array2<T>`, which is especially helpful when
@@ -134,51 +379,4 @@ pub mod aoc {
     }
 }
 
-pub mod aoc_grid {
-    use super::*;
-    use strum::EnumIter;
-
-    #[derive(PartialEq, Eq, Copy, Clone, Debug, EnumIter, Hash)]
-    pub enum Dir {
-        N,
-        E,
-        S,
-        W,
-    }
-
-    impl Dir {
-        pub fn opposite(self) -> Self {
-            match self {
-                Dir::N => Dir::S,
-                Dir::E => Dir::W,
-                Dir::S => Dir::N,
-                Dir::W => Dir::E,
-            }
-        }
-    }
-
-    #[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash, From)]
-    pub struct Pos(pub usize, pub usize);
-
-    impl Pos {
-        pub fn contains(self, other: Self) -> bool {
-            (0..self.0).contains(&other.0) && (0..self.1).contains(&other.1)
-        }
-
-        pub fn walk(self, direction: Dir, dim: Pos) -> Option<Self> {
-            let Pos(row, col) = self;
-            let (row, col) = match direction {
-                Dir::N => (row.checked_sub(1), Some(col)),
-                Dir::E => (Some(row), col.checked_add(1)),
-                Dir::S => (row.checked_add(1), Some(col)),
-                Dir::W => (Some(row), col.checked_sub(1)),
-            };
-            if let (Some(row), Some(col)) = (row, col) {
-                let pos = Pos(row, col);
-                dim.contains(pos).then_some(pos)
-            } else {
-                None
-            }
-        }
-    }
-}
+pub mod aoc_grid;
@@ -0,0 +1,151 @@
+//! Many puzzles ask for the state of some process after a ridiculous number
+//! of steps. Usually, the process ends up going round in circles, so we can
+//! find the cycle and skip ahead instead of simulating every step.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The sequence of states `init`, `step(init)`, `step(step(init))`, ...
+/// consists of a `lead` of states that are never visited again, followed by
+/// a loop of `period` states that repeats forever.
+#[derive(Debug, Clone)]
+pub struct Cycle<T> {
+    pub lead: usize,
+    pub period: usize,
+    /// The first `lead + period` states, which are all the states there are
+    states: Vec<T>,
+}
+
+impl<T> Cycle<T> {
+    /// The index into `states` of the state after `n` steps
+    pub fn reduce(&self, n: u64) -> usize {
+        let (lead, period) = (self.lead as u64, self.period as u64);
+        if n < lead {
+            n as usize
+        } else {
+            (lead + (n - lead) % period) as usize
+        }
+    }
+
+    /// The state after `n` steps
+    pub fn nth(&self, n: u64) -> &T {
+        &self.states[self.reduce(n)]
+    }
+
+    /// How many of the first `n` states, counting `init` as the first, are
+    /// `states()[k]`
+    pub fn visits(&self, k: usize, n: u64) -> u64 {
+        let (lead, period) = (self.lead as u64, self.period as u64);
+        match k as u64 {
+            k if k < lead => (k < n) as u64,
+            _ if n <= lead => 0,
+            k => (n - lead) / period + ((k - lead) < (n - lead) % period) as u64,
+        }
+    }
+
+    /// All distinct states, in the order they're first visited
+    pub fn states(&self) -> &[T] {
+        &self.states
+    }
+}
+
+/// Find the cycle by remembering every state we've seen in a `HashMap`. This
+/// steps through every state only once.
+pub fn find<T: Hash + Eq + Clone>(init: T, step: impl FnMut(&T) -> T) -> Cycle<T> {
+    find_within(init, step, u64::MAX).expect("ran out of states")
+}
+
+/// Like `find`, but give up if the first `limit` states are all different.
+/// That's for processes that may never go round in circles, or not before
+/// we're no longer interested in them. `step` is called once for each of
+/// those states.
+pub fn find_within<T: Hash + Eq + Clone>(
+    init: T,
+    mut step: impl FnMut(&T) -> T,
+    limit: u64,
+) -> Option<Cycle<T>> {
+    let mut seen = HashMap::new();
+    let mut states = vec![];
+    let mut state = init;
+    loop {
+        if let Some(&lead) = seen.get(&state) {
+            let period = states.len() - lead;
+            return Some(Cycle {
+                lead,
+                period,
+                states,
+            });
+        }
+        if states.len() as u64 == limit {
+            return None;
+        }
+        seen.insert(state.clone(), states.len());
+        let next = step(&state);
+        states.push(std::mem::replace(&mut state, next));
+    }
+}
+
+/// Find the cycle with Brent's algorithm, which only needs to compare states
+/// for equality. It does step through the states a few times over, so `find`
+/// is usually faster if the states are `Hash`.
+pub fn brent<T: Eq + Clone>(init: T, mut step: impl FnMut(&T) -> T) -> Cycle<T> {
+    // First find the period, by letting the hare run ahead in ever longer
+    // stretches until it catches up with the tortoise.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = init.clone();
+    let mut hare = step(&init);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // Then find the lead, by starting a tortoise and a hare `period` steps
+    // apart, and waiting until they meet.
+    let mut tortoise = init.clone();
+    let mut hare = init.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut lead = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        lead += 1;
+    }
+
+    let states = itertools::iterate(init, step).take(lead + period).collect();
+    Cycle {
+        lead,
+        period,
+        states,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_and_brent_agree() {
+        // 0, 1, 2, 3, 4, 5, 6, 7, 3, 4, ...
+        let step = |&x: &u32| if x == 7 { 3 } else { x + 1 };
+        for cycle in [find(0, step), brent(0, step)] {
+            assert_eq!((cycle.lead, cycle.period), (3, 5));
+            assert_eq!(cycle.states(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+            assert_eq!(*cycle.nth(2), 2);
+            assert_eq!(*cycle.nth(8), 3);
+            assert_eq!(*cycle.nth(1_000_000_000_000_000_000), 5);
+            assert_eq!((0..8).map(|k| cycle.visits(k, 2)).sum::<u64>(), 2);
+            assert_eq!(cycle.visits(4, 100), 20);
+            assert_eq!(cycle.visits(5, 100), 19);
+        }
+        assert!(find_within(0, step, 7).is_none());
+        assert!(find_within(0, step, 8).is_some());
+    }
+}
@@ -0,0 +1,294 @@
+//! Sets of integers made of a few long runs, and functions that shift such
+//! runs around. Puzzles like 2023 day 5 deal with ranges far too large to
+//! handle one number at a time.
+
+use itertools::Itertools;
+
+/// The half-open interval `lb..ub`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Interval {
+    pub lb: i64,
+    pub ub: i64,
+}
+
+impl Interval {
+    pub const fn new(lb: i64, ub: i64) -> Self {
+        Interval { lb, ub }
+    }
+
+    /// The closed interval `lo..=hi`
+    pub const fn inclusive(lo: i64, hi: i64) -> Self {
+        Interval::new(lo, hi + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lb >= self.ub
+    }
+
+    pub fn len(&self) -> i64 {
+        (self.ub - self.lb).max(0)
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        (self.lb..self.ub).contains(&x)
+    }
+
+    /// Whether every number in `other` is also in `self`
+    pub fn covers(&self, other: &Interval) -> bool {
+        other.is_empty() || (self.lb <= other.lb && other.ub <= self.ub)
+    }
+
+    /// Whether `self` and `other` have any number in common
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The numbers in both `self` and `other`, which may well be empty
+    pub fn intersection(&self, other: &Interval) -> Interval {
+        Interval::new(self.lb.max(other.lb), self.ub.min(other.ub))
+    }
+
+    /// Move the whole interval by `offset`
+    pub fn shift(&self, offset: i64) -> Interval {
+        Interval::new(self.lb + offset, self.ub + offset)
+    }
+}
+
+/// A set of integers, kept as a sorted list of disjoint intervals that
+/// aren't empty and don't touch.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The total number of integers in the set
+    pub fn len(&self) -> i64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|i| i.lb)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.intervals.last().map(|i| i.ub - 1)
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        self.intervals.iter().any(|i| i.contains(x))
+    }
+
+    /// Whether every number in `other` is also in `self`
+    pub fn covers(&self, other: &IntervalSet) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.intervals
+            .iter()
+            .chain(other.intervals.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        self.intervals
+            .iter()
+            .cartesian_product(other.intervals.iter())
+            .map(|(a, b)| a.intersection(b))
+            .collect()
+    }
+
+    /// The numbers in `self` that aren't in `other`
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = vec![];
+        for a in self.intervals.iter() {
+            let mut lb = a.lb;
+            for b in other.intervals.iter().filter(|b| b.overlaps(a)) {
+                out.push(Interval::new(lb, b.lb));
+                lb = b.ub;
+            }
+            out.push(Interval::new(lb, a.ub));
+        }
+        out.into_iter().collect()
+    }
+
+    /// Move the whole set by `offset`
+    pub fn shift(&self, offset: i64) -> IntervalSet {
+        let intervals = self.intervals.iter().map(|i| i.shift(offset)).collect();
+        IntervalSet { intervals }
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        [interval].into_iter().collect()
+    }
+}
+
+/// Collects any old intervals, sorting and merging them as needed
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let intervals = iter
+            .into_iter()
+            .filter(|i| !i.is_empty())
+            .sorted()
+            .coalesce(|a, b| {
+                if b.lb <= a.ub {
+                    Ok(Interval::new(a.lb, a.ub.max(b.ub)))
+                } else {
+                    Err((a, b))
+                }
+            })
+            .collect();
+        IntervalSet { intervals }
+    }
+}
+
+/// A function on the integers that shifts some disjoint intervals by a fixed
+/// offset each, and leaves all other numbers where they are.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PiecewiseMap {
+    /// Sorted, disjoint intervals, with their offsets
+    pieces: Vec<(Interval, i64)>,
+}
+
+impl PiecewiseMap {
+    /// The identity function
+    pub fn new() -> Self {
+        PiecewiseMap::default()
+    }
+
+    pub fn pieces(&self) -> &[(Interval, i64)] {
+        &self.pieces
+    }
+
+    /// The offset by which `x` is shifted
+    pub fn offset(&self, x: i64) -> i64 {
+        self.piece(x).map_or(0, |(_, offset)| offset)
+    }
+
+    fn piece(&self, x: i64) -> Option<(Interval, i64)> {
+        let i = self
+            .pieces
+            .partition_point(|(interval, _)| interval.ub <= x);
+        self.pieces
+            .get(i)
+            .filter(|(interval, _)| interval.contains(x))
+            .copied()
+    }
+
+    pub fn apply(&self, x: i64) -> i64 {
+        x + self.offset(x)
+    }
+
+    /// The image of a whole set of numbers
+    pub fn apply_set(&self, set: &IntervalSet) -> IntervalSet {
+        let sources: IntervalSet = self.pieces.iter().map(|&(interval, _)| interval).collect();
+        let moved = self.pieces.iter().flat_map(|&(interval, offset)| {
+            let moved = set.intersection(&interval.into()).shift(offset);
+            moved.intervals
+        });
+        let unmoved = set.difference(&sources).intervals;
+        moved.chain(unmoved).collect()
+    }
+
+    /// The function that first applies `self`, and then `next`
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        // The composition can only change its offset where `self` does, or
+        // where `self` maps a number to a point where `next` does.
+        let breakpoints = |pieces: &[(Interval, i64)]| {
+            pieces.iter().flat_map(|(i, _)| [i.lb, i.ub]).collect_vec()
+        };
+        let mut points = breakpoints(&self.pieces);
+        for b in breakpoints(&next.pieces) {
+            if self.piece(b).is_none() {
+                points.push(b);
+            }
+            let preimages = self.pieces.iter().map(|&(i, offset)| (i, b - offset));
+            points.extend(preimages.filter(|(i, x)| i.contains(*x)).map(|(_, x)| x));
+        }
+        points.sort();
+        points.dedup();
+        points
+            .into_iter()
+            .tuple_windows()
+            .map(|(lb, ub)| (Interval::new(lb, ub), next.apply(self.apply(lb)) - lb))
+            .collect()
+    }
+}
+
+/// Collects pieces, which must be disjoint. Adjacent pieces with the same
+/// offset are merged, and pieces that don't move anything are left out.
+impl FromIterator<(Interval, i64)> for PiecewiseMap {
+    fn from_iter<I: IntoIterator<Item = (Interval, i64)>>(iter: I) -> Self {
+        let pieces = iter
+            .into_iter()
+            .filter(|(interval, offset)| !interval.is_empty() && *offset != 0)
+            .sorted()
+            .coalesce(|(a, x), (b, y)| {
+                if a.ub == b.lb && x == y {
+                    Ok((Interval::new(a.lb, b.ub), x))
+                } else {
+                    Err(((a, x), (b, y)))
+                }
+            })
+            .collect_vec();
+        debug_assert!(pieces.windows(2).all(|w| w[0].0.ub <= w[1].0.lb));
+        PiecewiseMap { pieces }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(lb, ub)| Interval::new(lb, ub))
+            .collect()
+    }
+
+    #[test]
+    fn interval_sets() {
+        let a = set(&[(5, 10), (0, 3), (2, 4), (10, 12)]);
+        assert_eq!(a, set(&[(0, 4), (5, 12)]));
+        assert_eq!(a.len(), 11);
+        let b = set(&[(3, 6), (11, 20)]);
+        assert_eq!(a.union(&b), set(&[(0, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(3, 4), (5, 6), (11, 12)]));
+        assert_eq!(a.difference(&b), set(&[(0, 3), (6, 11)]));
+        assert!(a.covers(&set(&[(1, 3), (6, 12)])));
+        assert!(!a.covers(&b));
+        assert!(a.contains(11) && !a.contains(4));
+    }
+
+    #[test]
+    fn compose_maps() {
+        let f: PiecewiseMap = [(Interval::new(0, 10), 5)].into_iter().collect();
+        let g: PiecewiseMap = [(Interval::new(8, 12), -8)].into_iter().collect();
+        let h = f.then(&g);
+        for x in -5..20 {
+            assert_eq!(h.apply(x), g.apply(f.apply(x)), "at {x}");
+        }
+        let image = h.apply_set(&set(&[(-2, 15)]));
+        let expected: IntervalSet = (-2..15)
+            .map(|x| Interval::new(h.apply(x), h.apply(x) + 1))
+            .collect();
+        assert_eq!(image, expected);
+    }
+}
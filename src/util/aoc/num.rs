@@ -0,0 +1,129 @@
+//! A bit of number theory, for puzzles where several things go round in
+//! circles at different speeds and we want to know when they line up.
+
+/// Greatest common divisor
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, or `None` if it doesn't fit in a `u128`
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Extended Euclid: returns `(g, x, y)` such that `g` is the greatest common
+/// divisor of `a` and `b`, and `a * x + b * y == g`.
+pub fn egcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+/// The `x` in `0..m` such that `a * x ≡ 1 (mod m)`, if `m` is positive and
+/// `a` and `m` are coprime
+pub fn mod_inv(a: i128, m: i128) -> Option<i128> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = egcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Why `crt` couldn't come up with an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    /// The congruences contradict each other
+    NoSolution,
+    /// A modulus that isn't positive
+    BadModulus(i128),
+    /// The numbers got too large for an `i128`
+    Overflow,
+}
+
+impl std::fmt::Display for CrtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrtError::NoSolution => write!(f, "the congruences have no solution"),
+            CrtError::BadModulus(m) => write!(f, "modulus {m} isn't positive"),
+            CrtError::Overflow => write!(f, "the numbers don't fit in an i128"),
+        }
+    }
+}
+
+impl std::error::Error for CrtError {}
+
+/// Chinese remainder theorem: given congruences `x ≡ r (mod m)` as pairs
+/// `(r, m)`, find the `x` and `m` such that the congruences hold exactly
+/// for `x`, `x + m`, `x + 2m`, ... with `x` in `0..m`. The moduli needn't be
+/// coprime, so there may be no solution.
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Result<(i128, i128), CrtError> {
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(r1, m1), (r2, m2)| {
+            if m2 <= 0 {
+                return Err(CrtError::BadModulus(m2));
+            }
+            let (g, p, _) = egcd(m1, m2);
+            // Both remainders are in range, so this can't overflow
+            let diff = r2.rem_euclid(m2) - r1;
+            if diff % g != 0 {
+                return Err(CrtError::NoSolution);
+            }
+            // x = r1 + m1 * k, where m1 * k ≡ diff (mod m2)
+            let m2_g = m2 / g;
+            let k = (diff / g % m2_g)
+                .checked_mul(p % m2_g)
+                .ok_or(CrtError::Overflow)?
+                .rem_euclid(m2_g);
+            let m = m1.checked_mul(m2_g).ok_or(CrtError::Overflow)?;
+            // k < m2_g, so m1 * k < m
+            Ok(((r1 + m1 * k).rem_euclid(m), m))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_theory() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(u128::MAX, 2), None);
+        let (g, x, y) = egcd(240, 46);
+        assert_eq!((g, 240 * x + 46 * y), (2, 2));
+        assert_eq!(mod_inv(3, 11), Some(4));
+        assert_eq!(mod_inv(4, 10), None);
+        assert_eq!(mod_inv(3, 0), None);
+        assert_eq!(mod_inv(3, -11), None);
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt([(3, 4), (5, 6)]), Ok((11, 12)));
+        assert_eq!(crt([(0, 4), (1, 6)]), Err(CrtError::NoSolution));
+        assert_eq!(crt([(-1, 4)]), Ok((3, 4)));
+        assert_eq!(crt([(1, 3), (0, 0)]), Err(CrtError::BadModulus(0)));
+        assert_eq!(crt([(1, 3), (0, -5)]), Err(CrtError::BadModulus(-5)));
+        let big = 1 << 100;
+        assert_eq!(crt([(1, big), (2, big + 1)]), Err(CrtError::Overflow));
+        assert_eq!(
+            crt([(1, big), (2, 3_i128.pow(63))]),
+            Err(CrtError::Overflow)
+        );
+    }
+}
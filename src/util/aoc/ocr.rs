@@ -0,0 +1,355 @@
+//! Some puzzles draw their answer as letters made of `#` on a screen. This
+//! reads those letters back, so that the answer can be typed in, or compared
+//! against a manifest, as a plain string.
+
+use anyhow::anyhow;
+use itertools::Itertools;
+
+/// A fixed-width font: every glyph is `width` columns wide and `height`
+/// rows high, and glyphs start every `stride` columns.
+pub struct Font {
+    pub width: usize,
+    pub height: usize,
+    pub stride: usize,
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+/// The font used by most puzzles, like 2016 day 8 and 2022 day 10. There's
+/// no `Y`, since it's 5 columns wide and would run into its neighbours.
+pub const SMALL: Font = Font {
+    width: 4,
+    height: 6,
+    stride: 5,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+/// The taller font used by 2018 day 10
+#[rustfmt::skip]
+pub const LARGE: Font = Font {
+    width: 6,
+    height: 10,
+    stride: 8,
+    glyphs: &[
+        ('A', &[
+            "..##..",
+            ".#..#.",
+            "#....#",
+            "#....#",
+            "#....#",
+            "######",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#....#",
+        ]),
+        ('B', &[
+            "#####.",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#####.",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#####.",
+        ]),
+        ('C', &[
+            ".####.",
+            "#....#",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#....#",
+            ".####.",
+        ]),
+        ('E', &[
+            "######",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#####.",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "######",
+        ]),
+        ('F', &[
+            "######",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#####.",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+        ]),
+        ('G', &[
+            ".####.",
+            "#....#",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#..###",
+            "#....#",
+            "#....#",
+            "#...##",
+            ".###.#",
+        ]),
+        ('H', &[
+            "#....#",
+            "#....#",
+            "#....#",
+            "#....#",
+            "######",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#....#",
+        ]),
+        ('J', &[
+            "...###",
+            "....#.",
+            "....#.",
+            "....#.",
+            "....#.",
+            "....#.",
+            "....#.",
+            "#...#.",
+            "#...#.",
+            ".###..",
+        ]),
+        ('K', &[
+            "#....#",
+            "#...#.",
+            "#..#..",
+            "#.#...",
+            "##....",
+            "##....",
+            "#.#...",
+            "#..#..",
+            "#...#.",
+            "#....#",
+        ]),
+        ('L', &[
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "######",
+        ]),
+        ('N', &[
+            "#....#",
+            "##...#",
+            "##...#",
+            "#.#..#",
+            "#.#..#",
+            "#..#.#",
+            "#..#.#",
+            "#...##",
+            "#...##",
+            "#....#",
+        ]),
+        ('P', &[
+            "#####.",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#####.",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+            "#.....",
+        ]),
+        ('R', &[
+            "#####.",
+            "#....#",
+            "#....#",
+            "#....#",
+            "#####.",
+            "#..#..",
+            "#...#.",
+            "#...#.",
+            "#....#",
+            "#....#",
+        ]),
+        ('X', &[
+            "#....#",
+            "#....#",
+            ".#..#.",
+            ".#..#.",
+            "..##..",
+            "..##..",
+            ".#..#.",
+            ".#..#.",
+            "#....#",
+            "#....#",
+        ]),
+        ('Z', &[
+            "######",
+            ".....#",
+            ".....#",
+            "....#.",
+            "...#..",
+            "..#...",
+            ".#....",
+            "#.....",
+            "#.....",
+            "######",
+        ]),
+    ],
+};
+
+/// Read the letters in a bitmap, picking the font that fits its height. Any
+/// character other than `#` counts as blank.
+pub fn read(bitmap: &str) -> anyhow::Result<String> {
+    let rows = bitmap.trim_matches('\n').lines().count();
+    [&SMALL, &LARGE]
+        .into_iter()
+        .find(|font| font.height == rows)
+        .ok_or_else(|| anyhow!("no font is {rows} rows high"))?
+        .read(bitmap)
+}
+
+impl Font {
+    /// Read the letters in a bitmap drawn in this font
+    pub fn read(&self, bitmap: &str) -> anyhow::Result<String> {
+        let rows = bitmap
+            .trim_matches('\n')
+            .lines()
+            .map(|line| line.chars().map(|c| c == '#').collect_vec())
+            .collect_vec();
+        if rows.len() != self.height {
+            return Err(anyhow!(
+                "expected {} rows, but the bitmap has {}",
+                self.height,
+                rows.len()
+            ));
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        (0..columns)
+            .step_by(self.stride)
+            .map(|start| self.glyph(&rows, start))
+            .enumerate()
+            .filter(|(_, glyph)| glyph.iter().any(|&lit| lit))
+            .map(|(i, glyph)| {
+                self.decode(&glyph).ok_or_else(|| {
+                    anyhow!(
+                        "unknown glyph at position {} (columns {}..{}):\n{}",
+                        i + 1,
+                        i * self.stride,
+                        i * self.stride + self.width,
+                        self.draw(&glyph)
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Whether a bitmap could be letters in this font: it's high enough, and
+    /// nothing is drawn in the gaps between glyphs. That tells a failed read
+    /// apart from a picture that wasn't meant to be read in the first place.
+    pub fn fits(&self, bitmap: &str) -> bool {
+        let rows = bitmap.trim_matches('\n').lines().collect_vec();
+        rows.len() == self.height
+            && rows.iter().all(|row| {
+                row.chars()
+                    .enumerate()
+                    .all(|(col, c)| c != '#' || col % self.stride < self.width)
+            })
+    }
+
+    /// Cut out the glyph starting at column `start`, row by row
+    fn glyph(&self, rows: &[Vec<bool>], start: usize) -> Vec<bool> {
+        rows.iter()
+            .flat_map(|row| (start..start + self.width).map(|col| row.get(col) == Some(&true)))
+            .collect()
+    }
+
+    fn decode(&self, glyph: &[bool]) -> Option<char> {
+        self.glyphs
+            .iter()
+            .find(|(_, art)| {
+                art.iter()
+                    .flat_map(|row| row.chars())
+                    .map(|c| c == '#')
+                    .eq(glyph.iter().copied())
+            })
+            .map(|&(c, _)| c)
+    }
+
+    fn draw(&self, glyph: &[bool]) -> String {
+        glyph
+            .chunks(self.width)
+            .map(|row| {
+                row.iter()
+                    .map(|&lit| if lit { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_both_fonts() {
+        let small = "\
+###..#..#.#....
+#..#.#..#.#....
+#..#.####.#....
+###..#..#.#....
+#....#..#.#....
+#....#..#.####.";
+        assert_eq!(read(small).unwrap(), "PHL");
+        assert!(SMALL.fits(small) && !LARGE.fits(small));
+        assert!(!SMALL.fits(&small.replace("#....", "#...#")));
+        let large = LARGE
+            .glyphs
+            .iter()
+            .filter(|(c, _)| "ZAX".contains(*c))
+            .map(|(_, art)| art)
+            .collect_vec();
+        let large = (0..LARGE.height)
+            .map(|row| large.iter().map(|art| art[row]).join(".."))
+            .join("\n");
+        assert_eq!(read(&large).unwrap(), "AXZ");
+        let error = read(&small.replace("###..#..#", "###..#.##")).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unknown glyph at position 2 (columns 5..9)"));
+    }
+}
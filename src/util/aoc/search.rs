@@ -0,0 +1,176 @@
+//! Shortest paths through graphs that are only given implicitly, by a
+//! function from a node to its neighbours. Nodes can be anything we can hash,
+//! like a position, or a position along with a direction.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// What we found out during a search: the cost of reaching each node we got
+/// to, and how we got there. If the search stopped early at a goal, costs of
+/// nodes that weren't expanded yet may not be the lowest possible.
+#[derive(Debug, Clone)]
+pub struct Search<N, C = usize> {
+    cost: HashMap<N, C>,
+    prev: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N: Hash + Eq + Clone, C: Copy> Search<N, C> {
+    fn new() -> Self {
+        Search {
+            cost: HashMap::new(),
+            prev: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    /// The goal node we stopped at, if any
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// The cost of reaching the goal node, if we found one
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.cost(goal))
+    }
+
+    /// The cost of reaching `node`, if we did
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.cost.get(node).copied()
+    }
+
+    /// All the nodes we reached
+    pub fn reached(&self) -> impl Iterator<Item = &N> {
+        self.cost.keys()
+    }
+
+    /// The nodes on the way from one of the sources to `node`, both included
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        self.cost.contains_key(node).then(|| {
+            let mut path = std::iter::successors(Some(node), |node| self.prev.get(node))
+                .cloned()
+                .collect::<Vec<_>>();
+            path.reverse();
+            path
+        })
+    }
+}
+
+/// Breadth-first search, for when every step costs the same. Stops as soon
+/// as we reach a node for which `goal` holds.
+pub fn bfs<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Search<N>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for source in sources {
+        if search.cost.insert(source.clone(), 0).is_none() {
+            queue.push_back(source);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        let cost = search.cost[&node] + 1;
+        for next in neighbours(&node) {
+            if !search.cost.contains_key(&next) {
+                search.cost.insert(next.clone(), cost);
+                search.prev.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's algorithm, for when steps have different (non-negative) costs.
+/// Stops as soon as we reach a node for which `goal` holds.
+pub fn dijkstra<N, C, I>(
+    sources: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(sources, neighbours, |_| C::default(), goal)
+}
+
+/// A* search, which is Dijkstra's algorithm steered towards the goal by a
+/// `heuristic`. For the result to be right, the heuristic must never
+/// overestimate the remaining cost.
+pub fn astar<N, C, I>(
+    sources: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut search = Search::new();
+    // The heap refers to nodes by their index in `nodes`, so that nodes
+    // needn't be `Ord`.
+    let mut nodes = vec![];
+    let mut queue = BinaryHeap::new();
+    for source in sources {
+        if search.cost.insert(source.clone(), C::default()).is_none() {
+            queue.push(Reverse((heuristic(&source), C::default(), nodes.len())));
+            nodes.push(source);
+        }
+    }
+    while let Some(Reverse((_, cost, i))) = queue.pop() {
+        let node = nodes[i].clone();
+        if search.cost[&node] < cost {
+            // We've since found a cheaper way to get here
+            continue;
+        }
+        if goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        for (next, step) in neighbours(&node) {
+            let cost = cost + step;
+            if search.cost.get(&next).is_none_or(|&old| cost < old) {
+                search.cost.insert(next.clone(), cost);
+                search.prev.insert(next.clone(), node.clone());
+                queue.push(Reverse((cost + heuristic(&next), cost, nodes.len())));
+                nodes.push(next);
+            }
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_a_line() {
+        // Walking costs 1 per step; jumping ahead 5 costs 3.
+        let neighbours = |&n: &i32| [(n + 1, 1), (n - 1, 1), (n + 5, 3)];
+        let search = dijkstra([0], neighbours, |&n| n == 10);
+        assert_eq!(search.goal_cost(), Some(6));
+        assert_eq!(search.path(&10), Some(vec![0, 5, 10]));
+        let search = astar([0], neighbours, |&n| (10 - n).abs() * 3 / 5, |&n| n == 10);
+        assert_eq!(search.goal_cost(), Some(6));
+        let search = bfs([0, 100], |&n| [n + 1, n + 5], |&n| n == 12);
+        assert_eq!(search.goal_cost(), Some(4));
+        assert_eq!(search.path(&12).map(|p| p[0]), Some(0));
+    }
+}
@@ -0,0 +1,556 @@
+//! Positions, directions, and rectangular grids of tiles. Most grid puzzles
+//! boil down to parsing a block of characters into a `Grid<Tile>`, and then
+//! walking around in it.
+
+use super::*;
+use ndarray::Array2;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// A system of directions to walk in, like the four points of the compass.
+/// `ALL` lists the directions clockwise, which is all we need for turning.
+pub trait Direction: Copy + Eq + 'static {
+    const ALL: &'static [Self];
+
+    /// The change in (row, column) for a single step in this direction
+    fn delta(self) -> (isize, isize);
+
+    /// Turn clockwise by `k` steps, or widdershins if `k` is negative
+    fn rotate(self, k: isize) -> Self {
+        let n = Self::ALL.len() as isize;
+        let i = Self::ALL.iter().position(|&d| d == self).unwrap_or(0) as isize;
+        Self::ALL[(i + k).rem_euclid(n) as usize]
+    }
+
+    fn turn_right(self) -> Self {
+        self.rotate(1)
+    }
+
+    fn turn_left(self) -> Self {
+        self.rotate(-1)
+    }
+
+    fn opposite(self) -> Self {
+        self.rotate(Self::ALL.len() as isize / 2)
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, EnumIter, Hash)]
+pub enum Dir {
+    N,
+    E,
+    S,
+    W,
+}
+
+impl Direction for Dir {
+    const ALL: &'static [Self] = &[Dir::N, Dir::E, Dir::S, Dir::W];
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Dir::N => (-1, 0),
+            Dir::E => (0, 1),
+            Dir::S => (1, 0),
+            Dir::W => (0, -1),
+        }
+    }
+}
+
+/// The four points of the compass, and the four in between
+#[derive(PartialEq, Eq, Copy, Clone, Debug, EnumIter, Hash)]
+pub enum Dir8 {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction for Dir8 {
+    const ALL: &'static [Self] = &[
+        Dir8::N,
+        Dir8::NE,
+        Dir8::E,
+        Dir8::SE,
+        Dir8::S,
+        Dir8::SW,
+        Dir8::W,
+        Dir8::NW,
+    ];
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Dir8::N => (-1, 0),
+            Dir8::NE => (-1, 1),
+            Dir8::E => (0, 1),
+            Dir8::SE => (1, 1),
+            Dir8::S => (1, 0),
+            Dir8::SW => (1, -1),
+            Dir8::W => (0, -1),
+            Dir8::NW => (-1, -1),
+        }
+    }
+}
+
+impl From<Dir> for Dir8 {
+    fn from(dir: Dir) -> Self {
+        match dir {
+            Dir::N => Dir8::N,
+            Dir::E => Dir8::E,
+            Dir::S => Dir8::S,
+            Dir::W => Dir8::W,
+        }
+    }
+}
+
+/// Directions on a hex grid with pointy-topped cells, so that the cells form
+/// rows. Steps are given in axial coordinates: the row is `r`, and the column
+/// is `q`, which runs along the row. Going NE or SW changes both.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, EnumIter, Hash)]
+pub enum HexDir {
+    NE,
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction for HexDir {
+    const ALL: &'static [Self] = &[
+        HexDir::NE,
+        HexDir::E,
+        HexDir::SE,
+        HexDir::SW,
+        HexDir::W,
+        HexDir::NW,
+    ];
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            HexDir::NE => (-1, 1),
+            HexDir::E => (0, 1),
+            HexDir::SE => (1, 0),
+            HexDir::SW => (1, -1),
+            HexDir::W => (0, -1),
+            HexDir::NW => (-1, 0),
+        }
+    }
+}
+
+/// A cell on an unbounded hex grid, in axial coordinates
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Hex {
+    pub fn walk(self, direction: HexDir) -> Self {
+        let (r, q) = direction.delta();
+        Hex {
+            q: self.q + q as i64,
+            r: self.r + r as i64,
+        }
+    }
+
+    pub fn neighbours(self) -> impl Iterator<Item = Hex> {
+        HexDir::iter().map(move |direction| self.walk(direction))
+    }
+
+    /// The number of steps it takes to walk from `self` to `other`
+    pub fn distance(self, other: Self) -> u64 {
+        let (q, r) = (self.q - other.q, self.r - other.r);
+        (q.unsigned_abs() + r.unsigned_abs() + (q + r).unsigned_abs()) / 2
+    }
+}
+
+/// A (row, column) position in a grid
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash, From)]
+pub struct Pos(pub usize, pub usize);
+
+impl Pos {
+    pub fn contains(self, other: Self) -> bool {
+        (0..self.0).contains(&other.0) && (0..self.1).contains(&other.1)
+    }
+
+    pub fn walk(self, direction: impl Direction, dim: Pos) -> Option<Self> {
+        self.step(direction.delta(), dim)
+    }
+
+    /// Move by `(rows, cols)`, as long as we stay within `dim`
+    pub fn step(self, (rows, cols): (isize, isize), dim: Pos) -> Option<Self> {
+        let Pos(row, col) = self;
+        let pos = Pos(row.checked_add_signed(rows)?, col.checked_add_signed(cols)?);
+        dim.contains(pos).then_some(pos)
+    }
+}
+
+impl From<Pos> for (usize, usize) {
+    fn from(Pos(row, col): Pos) -> Self {
+        (row, col)
+    }
+}
+
+/// A rectangular grid of tiles, indexed by `Pos`. The underlying `Array2` is
+/// available through `Deref` for anything fancier, like slicing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deref, DerefMut, From)]
+pub struct Grid<T>(Array2<T>);
+
+impl<T> Grid<T> {
+    /// Parse a block of text into a grid, one line per row, turning each
+    /// character into a tile with `tile`.
+    pub fn parse(s: &str, mut tile: impl FnMut(char) -> anyhow::Result<T>) -> anyhow::Result<Self> {
+        let rows = s
+            .lines()
+            .enumerate()
+            .map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(col, c)| {
+                        tile(c).map_err(|e| {
+                            e.context(format!(
+                                "bad tile {c:?} at line {}, column {}",
+                                row + 1,
+                                col + 1
+                            ))
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<T>>>()
+            })
+            .collect::<anyhow::Result<Vec<Vec<T>>>>()?;
+        if let Some((row, _)) = rows.iter().find_position(|row| row.len() != rows[0].len()) {
+            return Err(anyhow!("line {} is not as long as the first", row + 1));
+        }
+        let shape = (rows.len(), rows.first().map_or(0, Vec::len));
+        let tiles = rows.into_iter().flatten().collect();
+        Ok(Grid(Array2::from_shape_vec(shape, tiles)?))
+    }
+
+    /// A grid of the given size, filled with copies of `t`
+    pub fn from_elem(size: Pos, t: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid(Array2::from_elem(<(usize, usize)>::from(size), t))
+    }
+
+    /// The number of rows and columns, as a `Pos` just past the corner
+    pub fn size(&self) -> Pos {
+        self.0.dim().into()
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.0.get(<(usize, usize)>::from(pos))
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.0.get_mut(<(usize, usize)>::from(pos))
+    }
+
+    /// All positions, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let (rows, cols) = self.0.dim();
+        itertools::iproduct!(0..rows, 0..cols).map(Pos::from)
+    }
+
+    /// All tiles along with their positions, row by row
+    pub fn tiles(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.0.indexed_iter().map(|(pos, t)| (pos.into(), t))
+    }
+
+    /// The position of the first tile that satisfies `p`
+    pub fn find(&self, mut p: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.tiles().find(|(_, t)| p(t)).map(|(pos, _)| pos)
+    }
+
+    /// The positions adjacent to `pos` in each of the directions `D`
+    pub fn neighbours<D: Direction>(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        let size = self.size();
+        D::ALL.iter().filter_map(move |&dir| pos.walk(dir, size))
+    }
+
+    /// The (up to) 4 positions orthogonally adjacent to `pos`
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        self.neighbours::<Dir>(pos)
+    }
+
+    /// The (up to) 8 positions orthogonally or diagonally adjacent to `pos`
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        self.neighbours::<Dir8>(pos)
+    }
+
+    /// The positions passed when walking from `pos` in a straight line, not
+    /// including `pos` itself, until we fall off the grid. With a `Dir8`,
+    /// this gives diagonals too.
+    pub fn ray(&self, pos: Pos, dir: impl Direction) -> impl Iterator<Item = Pos> {
+        let size = self.size();
+        itertools::iterate(Some(pos), move |pos| {
+            pos.and_then(|pos| pos.walk(dir, size))
+        })
+        .skip(1)
+        .while_some()
+    }
+
+    /// Every position on the edge of the grid, paired with the direction
+    /// that points into the grid from there. Corners appear twice.
+    pub fn edges(&self) -> impl Iterator<Item = (Pos, Dir)> {
+        let Pos(rows, cols) = self.size();
+        let (last_row, last_col) = (rows.saturating_sub(1), cols.saturating_sub(1));
+        itertools::chain!(
+            (0..rows).map(|row| (Pos(row, 0), Dir::E)),
+            (0..rows).map(move |row| (Pos(row, last_col), Dir::W)),
+            (0..cols).map(|col| (Pos(0, col), Dir::S)),
+            (0..cols).map(move |col| (Pos(last_row, col), Dir::N)),
+        )
+    }
+}
+
+impl<T> FromStr for Grid<T>
+where
+    T: TryFrom<char>,
+    T::Error: Into<anyhow::Error>,
+{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse(s, |c| T::try_from(c).map_err(Into::into))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, Pos(row, col): Pos) -> &T {
+        &self.0[(row, col)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, Pos(row, col): Pos) -> &mut T {
+        &mut self.0[(row, col)]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.0.rows() {
+            for t in row {
+                write!(f, "{t}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A point on an unbounded plane. Like `Pos`, `x` grows to the right (E)
+/// and `y` grows downwards (S), so that the two convert easily.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Point<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0, 0);
+
+    /// Take a single step in some direction
+    pub fn walk(self, direction: impl Direction) -> Self {
+        let (y, x) = direction.delta();
+        self + Point::new(x as i64, y as i64)
+    }
+
+    /// The distance when moving only orthogonally
+    pub fn manhattan(self, other: Self) -> i64 {
+        let d = self - other;
+        d.x.abs() + d.y.abs()
+    }
+
+    /// The distance when diagonal moves are allowed too
+    pub fn chebyshev(self, other: Self) -> i64 {
+        let d = self - other;
+        max(d.x.abs(), d.y.abs())
+    }
+
+    /// A step of at most 1 in each coordinate, in the direction of `self`
+    pub fn signum(self) -> Self {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+
+    /// The point at `pos` in a grid whose top left corner is at `origin`
+    pub fn from_pos(Pos(row, col): Pos, origin: Point) -> Self {
+        origin + Point::new(col as i64, row as i64)
+    }
+
+    /// The position of this point in a grid whose top left corner is at
+    /// `origin`, if it isn't above or left of that corner
+    pub fn to_pos(self, origin: Point) -> Option<Pos> {
+        let Point { x, y } = self - origin;
+        Some(Pos(y.try_into().ok()?, x.try_into().ok()?))
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, k: T) -> Self {
+        Point::new(self.x * k, self.y * k)
+    }
+}
+
+impl<T: Add<Output = T> + Copy> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Sub<Output = T> + Copy> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+/// A sparse grid of tiles that stretches out in all directions. Only the
+/// tiles that have been set take up space. The underlying `HashMap` is
+/// available through `Deref`.
+#[derive(Debug, Clone, PartialEq, Eq, Deref, DerefMut)]
+pub struct InfiniteGrid<T>(HashMap<Point, T>);
+
+impl<T> InfiniteGrid<T> {
+    pub fn new() -> Self {
+        InfiniteGrid(HashMap::new())
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle that
+    /// contains every tile, or `None` if there aren't any
+    pub fn bbox(&self) -> Option<(Point, Point)> {
+        let (x_min, x_max) = self.0.keys().map(|p| p.x).minmax().into_option()?;
+        let (y_min, y_max) = self.0.keys().map(|p| p.y).minmax().into_option()?;
+        Some((Point::new(x_min, y_min), Point::new(x_max, y_max)))
+    }
+}
+
+impl<T> Default for InfiniteGrid<T> {
+    fn default() -> Self {
+        InfiniteGrid::new()
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for InfiniteGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        InfiniteGrid(iter.into_iter().collect())
+    }
+}
+
+/// Renders the bounding box, with `.` for tiles that haven't been set
+impl<T: Display> Display for InfiniteGrid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((min, max)) = self.bbox() else {
+            return Ok(());
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                match self.0.get(&Point::new(x, y)) {
+                    Some(t) => write!(f, "{t}")?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walk_around_grid() {
+        let grid: Grid<char> = "abc\ndef\n".parse().unwrap();
+        assert_eq!(grid.size(), Pos(2, 3));
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+        assert_eq!(grid.get(Pos(1, 2)), Some(&'f'));
+        assert_eq!(grid.get(Pos(2, 0)), None);
+        assert_eq!(grid.neighbours4(Pos(0, 0)).count(), 2);
+        assert_eq!(grid.neighbours8(Pos(1, 1)).count(), 5);
+        let ray = grid.ray(Pos(0, 0), Dir::E).map(|pos| grid[pos]);
+        assert_eq!(ray.collect::<String>(), "bc");
+        let ray = grid.ray(Pos(0, 0), Dir8::SE).map(|pos| grid[pos]);
+        assert_eq!(ray.collect::<String>(), "e");
+        assert!("ab\nc\n".parse::<Grid<char>>().is_err());
+    }
+
+    #[test]
+    fn turn_around() {
+        assert_eq!(Dir::N.turn_right(), Dir::E);
+        assert_eq!(Dir::N.turn_left(), Dir::W);
+        assert_eq!(Dir::E.opposite(), Dir::W);
+        assert_eq!(Dir8::N.rotate(3), Dir8::SE);
+        assert_eq!(Dir8::NE.opposite(), Dir8::SW);
+        assert_eq!(HexDir::NE.rotate(-7), HexDir::NW);
+        assert_eq!(HexDir::SE.opposite(), HexDir::NW);
+        let hex = Hex::default().walk(HexDir::NE).walk(HexDir::E);
+        assert_eq!(hex, Hex { q: 2, r: -1 });
+        assert_eq!(hex.distance(Hex::default()), 2);
+        assert!(HexDir::iter().all(|d| Hex::default().walk(d).walk(d.opposite()) == Hex::default()));
+    }
+
+    #[test]
+    fn points() {
+        let p = Point::new(3, -4);
+        assert_eq!(p + p * 2 - Point::new(9, 0), Point::new(0, -12));
+        assert_eq!(p.manhattan(Point::ORIGIN), 7);
+        assert_eq!(p.chebyshev(Point::ORIGIN), 4);
+        assert_eq!(p.signum(), Point::new(1, -1));
+        assert_eq!(Point::ORIGIN.walk(Dir::N).walk(Dir8::SE), Point::new(1, 0));
+        let origin = Point::new(-1, -1);
+        assert_eq!(Point::from_pos(Pos(2, 1), origin), Point::new(0, 1));
+        assert_eq!(Point::new(0, 1).to_pos(origin), Some(Pos(2, 1)));
+        assert_eq!(Point::new(-2, 1).to_pos(origin), None);
+        let grid: InfiniteGrid<char> = [(Point::new(-1, 0), '#'), (Point::new(1, 1), '#')]
+            .into_iter()
+            .collect();
+        assert_eq!(grid.bbox(), Some((Point::new(-1, 0), Point::new(1, 1))));
+        assert_eq!(grid.to_string(), "#..\n..#\n");
+    }
+}